@@ -0,0 +1,233 @@
+use {
+    super::app_dirs,
+    anyhow::{anyhow, bail, Context, Result},
+    std::{fs, path::PathBuf},
+};
+
+/// name of the configuration file, in the config directory of the app
+pub static CONFIG_FILE_NAME: &str = "csv2svg.toml";
+
+/// a value of the configuration. Numbers are kept as text, as
+/// they're given to the same parsers than the arguments
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValue {
+    Text(String),
+    Bool(bool),
+    List(Vec<String>),
+}
+
+/// options given as `key = value`, keys being the names of the
+/// long command line arguments
+pub type ConfigEntries = Vec<(String, ConfigValue)>;
+
+/// the content of the configuration file: default values of the
+/// arguments, and named sets of values
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    pub defaults: ConfigEntries,
+    pub presets: Vec<(String, ConfigEntries)>,
+}
+
+/// convert a TOML value to a value of the configuration
+fn config_value(value: toml::Value) -> Result<ConfigValue> {
+    match value {
+        toml::Value::Boolean(b) => Ok(ConfigValue::Bool(b)),
+        toml::Value::Array(items) => items
+            .into_iter()
+            .map(|item| match config_value(item)? {
+                ConfigValue::Text(text) => Ok(text),
+                _ => Err(anyhow!("only strings and numbers are allowed in arrays")),
+            })
+            .collect::<Result<_>>()
+            .map(ConfigValue::List),
+        toml::Value::String(s) => Ok(ConfigValue::Text(s)),
+        toml::Value::Integer(i) => Ok(ConfigValue::Text(i.to_string())),
+        toml::Value::Float(f) => Ok(ConfigValue::Text(f.to_string())),
+        toml::Value::Datetime(dt) => Ok(ConfigValue::Text(dt.to_string())),
+        toml::Value::Table(_) => Err(anyhow!("unexpected table")),
+    }
+}
+
+/// convert the entries of a TOML table, in which keys may be written
+/// with underscores instead of dashes
+fn config_entries(table: toml::Table) -> Result<ConfigEntries> {
+    table
+        .into_iter()
+        .map(|(key, value)| {
+            let value = config_value(value).with_context(|| format!("invalid value of {}", key))?;
+            Ok((key.replace('_', "-"), value))
+        })
+        .collect()
+}
+
+/// parse a TOML document whose values are options and whose tables
+/// are named sets of options, like `[presets.name]`, the kind of the
+/// tables being one of `kinds`. Return the options given before the
+/// first table, and the named tables
+pub fn parse_tables(
+    content: &str,
+    kinds: &[&str],
+) -> Result<(ConfigEntries, Vec<(String, ConfigEntries)>)> {
+    let root: toml::Table = content.parse()?;
+    let mut entries = toml::Table::new();
+    let mut tables = Vec::new();
+    for (key, value) in root {
+        match value {
+            toml::Value::Table(named_tables) if kinds.contains(&key.as_str()) => {
+                for (name, table) in named_tables {
+                    match table {
+                        toml::Value::Table(table) => {
+                            tables.push((name, config_entries(table)?));
+                        }
+                        _ => bail!("[{}.{}] should be a table", key, name),
+                    }
+                }
+            }
+            toml::Value::Table(_) => bail!("unexpected table [{}]", key),
+            value => {
+                entries.insert(key, value);
+            }
+        }
+    }
+    Ok((config_entries(entries)?, tables))
+}
+
+/// convert entries to command line arguments
+pub fn entries_args(entries: ConfigEntries) -> Vec<String> {
+    let mut args = Vec::new();
+    for (key, value) in entries {
+        let arg = format!("--{}", key);
+        match value {
+            ConfigValue::Bool(true) => {
+                args.push(arg);
+            }
+            ConfigValue::Bool(false) => {
+                // cancels the switch set in the entries it overrides
+                args.push(format!("--no-{}", key));
+            }
+            ConfigValue::Text(text) => {
+                args.push(arg);
+                args.push(text);
+            }
+            ConfigValue::List(texts) => {
+                for text in texts {
+                    args.push(arg.clone());
+                    args.push(text);
+                }
+            }
+        }
+    }
+    args
+}
+
+impl Config {
+    /// parse a configuration, whose tables are `[presets.name]`
+    pub fn parse(content: &str) -> Result<Self> {
+        let (defaults, presets) = parse_tables(content, &["presets", "preset"])?;
+        Ok(Self { defaults, presets })
+    }
+    pub fn path() -> Result<PathBuf> {
+        Ok(app_dirs()?.config_dir().join(CONFIG_FILE_NAME))
+    }
+    /// read the configuration file, if there's one
+    pub fn read() -> Result<Option<Self>> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("can't read config file {:?}", path))?;
+        let config =
+            Self::parse(&content).with_context(|| format!("invalid config file {:?}", path))?;
+        Ok(Some(config))
+    }
+    /// the values of the defaults, overridden by the ones of the given
+    /// preset, as command line arguments
+    pub fn args(&self, preset: Option<&str>) -> Result<Vec<String>> {
+        let mut entries = self.defaults.clone();
+        if let Some(name) = preset {
+            let (_, preset_entries) = self
+                .presets
+                .iter()
+                .find(|(n, _)| n == name)
+                .ok_or_else(|| anyhow!("no preset {:?} in the config", name))?;
+            for (key, value) in preset_entries {
+                entries.retain(|(k, _)| k != key);
+                entries.push((key.clone(), value.clone()));
+            }
+        }
+        Ok(entries_args(entries))
+    }
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+
+    static CONFIG: &str = r#"
+# defaults for the whole team
+theme = "light"
+size = '1200x400'
+y_fmt = "si" # underscores are like dashes
+legend-stats = true
+y-min = 0
+style = [
+    "total:color=#999,width=6",
+    "errors:color=#f00", # comma in string
+]
+
+[presets.latency]
+y-fmt = "fixed:1"
+hline = ["200:p99 SLO"]
+"#;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(CONFIG).unwrap();
+        assert_eq!(config.defaults.len(), 6);
+        assert_eq!(
+            config.defaults[2],
+            ("y-fmt".to_string(), ConfigValue::Text("si".to_string()))
+        );
+        assert_eq!(
+            config.defaults[5].1,
+            ConfigValue::List(vec![
+                "total:color=#999,width=6".to_string(),
+                "errors:color=#f00".to_string(),
+            ])
+        );
+        assert_eq!(config.presets.len(), 1);
+        assert!(Config::parse("theme").is_err());
+        assert!(Config::parse("theme = light").is_err());
+        assert!(Config::parse("[themes.dark]\ntheme = \"dark\"").is_err());
+        assert!(Config::parse("[presets]\nlatency = \"si\"").is_err());
+        assert!(Config::parse("size = { w = 800 }").is_err());
+    }
+    #[test]
+    fn test_config_args() {
+        let config = Config::parse(CONFIG).unwrap();
+        let args = config.args(Some("latency")).unwrap();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        assert_eq!(
+            args,
+            [
+                "--theme",
+                "light",
+                "--size",
+                "1200x400",
+                "--legend-stats",
+                "--y-min",
+                "0",
+                "--style",
+                "total:color=#999,width=6",
+                "--style",
+                "errors:color=#f00",
+                "--y-fmt",
+                "fixed:1",
+                "--hline",
+                "200:p99 SLO",
+            ]
+        );
+        assert!(config.args(Some("unknown")).is_err());
+    }
+}
@@ -1,3 +1,7 @@
+mod config;
+
+pub use config::*;
+
 use {anyhow::*, directories::ProjectDirs};
 
 /// return the instance of ProjectDirs holding the app specific paths
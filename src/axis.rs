@@ -0,0 +1,91 @@
+use crate::*;
+
+/// the side of the plot on which a y axis is labelled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    /// the short mark telling in the legend which axis a series uses
+    pub fn legend_mark(self) -> &'static str {
+        match self {
+            Self::Left => "(L)",
+            Self::Right => "(R)",
+        }
+    }
+}
+
+/// a y axis, with its own scale, used by some of the y series
+pub struct YAxis {
+    pub side: Side,
+    pub scale: Scale,
+    pub projector: Projector,
+}
+
+/// assign each y series to an axis depending on its order of magnitude:
+/// when there's a gap of more than one order of magnitude between the
+/// series, the smallest ones go to the left axis
+pub fn split_by_magnitude(tbl: &Tbl) -> Vec<Side> {
+    let magnitudes: Vec<f64> = tbl
+        .y_seqs()
+        .map(|seq| (seq.min.abs().max(seq.max.abs()).max(1) as f64).log10())
+        .collect();
+    let mut sorted = magnitudes.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    // we look for the biggest gap between two consecutive magnitudes
+    let mut threshold = None;
+    let mut biggest_gap = 1.0;
+    for idx in 1..sorted.len() {
+        let gap = sorted[idx] - sorted[idx - 1];
+        if gap >= biggest_gap {
+            biggest_gap = gap;
+            threshold = Some(sorted[idx]);
+        }
+    }
+    magnitudes
+        .iter()
+        .map(|&m| match threshold {
+            Some(threshold) if m < threshold => Side::Left,
+            _ => Side::Right,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod axis_tests {
+    use super::*;
+
+    fn tbl(cols: Vec<Vec<i64>>) -> Tbl {
+        let len = cols[0].len();
+        let mut seqs =
+            vec![Seq::from_integers("x".to_string(), (0..len as i64).map(Some).collect()).unwrap()];
+        for (idx, col) in cols.into_iter().enumerate() {
+            seqs.push(
+                Seq::from_integers(format!("y{}", idx), col.into_iter().map(Some).collect())
+                    .unwrap(),
+            );
+        }
+        Tbl::from_seqs(seqs).unwrap()
+    }
+
+    #[test]
+    fn test_split_by_magnitude() {
+        let tbl = tbl(vec![
+            vec![1200, 3400],
+            vec![3, 8],
+            vec![800, 7000],
+            vec![-2, 5],
+        ]);
+        assert_eq!(
+            split_by_magnitude(&tbl),
+            vec![Side::Right, Side::Left, Side::Right, Side::Left],
+        );
+    }
+    #[test]
+    fn test_no_split() {
+        let tbl = tbl(vec![vec![120, 340], vec![30, 80]]);
+        assert_eq!(split_by_magnitude(&tbl), vec![Side::Right, Side::Right]);
+    }
+}
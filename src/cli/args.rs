@@ -1,4 +1,10 @@
-use argh::FromArgs;
+use {
+    crate::*,
+    anyhow::{bail, Result},
+    argh::FromArgs,
+    chrono::DateTime,
+    std::path::PathBuf,
+};
 
 #[derive(Debug, FromArgs)]
 /// I need to explain this, I guess
@@ -9,9 +15,387 @@ pub struct Args {
     /// print the version
     pub version: bool,
 
+    #[argh(positional)]
+    /// csv file to read, instead of the standard input
+    pub file: Option<PathBuf>,
+
+    #[argh(option)]
+    /// name of a set of options defined in the config file
+    pub preset: Option<String>,
+
+    #[argh(option)]
+    /// csv file of a graph of a dashboard page, drawn with the other options
+    /// (may be repeated)
+    pub chart: Vec<PathBuf>,
+
+    #[argh(option)]
+    /// file describing the graphs of a dashboard page
+    pub dashboard: Option<PathBuf>,
+
+    #[argh(option)]
+    /// number of columns of the dashboard (default 2)
+    pub columns: Option<usize>,
+
     #[argh(option, short = 'f')]
     /// output format: "svg" or "html"
     pub format: Option<Format>,
+
+    #[argh(option, from_str_fn(parse_size))]
+    /// size of the graph, eg "1200x400" (default "800x500")
+    pub size: Option<(usize, usize)>,
+
+    #[argh(option)]
+    /// margins around the plot, as in CSS: "top,right,bottom,left", "vertical,horizontal"
+    /// or a single value
+    pub margins: Option<Margins>,
+
+    #[argh(option)]
+    /// sizing of the SVG: "fit" (default, takes the room it's given), "fixed" (the size
+    /// of the graph, in pixels), or "responsive" (the width of the container)
+    pub sizing: Option<SvgSizing>,
+
+    #[argh(option)]
+    /// preserveAspectRatio of the SVG, eg "xMidYMin meet" or "none" to stretch
+    pub aspect_ratio: Option<AspectRatio>,
+
+    #[argh(option)]
+    /// colors: "dark" (default), "light", "print", or "auto" to follow the preference
+    /// of the browser in HTML
+    pub theme: Option<ThemeName>,
+
+    #[argh(option)]
+    /// colors of the series: "default", "okabe-ito" or "viridis" (colorblind safe)
+    pub palette: Option<PaletteName>,
+
+    #[argh(switch)]
+    /// distinguish series with dash patterns and marker shapes too
+    pub patterns: bool,
+
+    #[argh(option)]
+    /// look of the series whose header matches a glob, eg "errors:color=#f00,width=2"
+    /// (keys: color, width, opacity, dash, marker, interpolation; may be repeated)
+    pub style: Vec<StyleRule>,
+
+    #[argh(option)]
+    /// file of series styles, one per line, overridden by --style
+    pub style_file: Option<PathBuf>,
+
+    #[argh(option)]
+    /// font of a kind of text, eg "title:family=Georgia, serif,size=18" (roles: all, title,
+    /// subtitle, caption, legend, tick, label; may be repeated)
+    pub font: Vec<FontSetting>,
+
+    #[argh(option)]
+    /// woff2, woff, ttf or otf file to embed in the SVG, declared as the first family
+    /// given with --font "all:family=..." or named after the file. The file is embedded
+    /// as is: subset it beforehand to keep the SVG small
+    pub embed_font: Option<PathBuf>,
+
+    #[argh(option)]
+    /// format of numeric x values: "plain", "thousands", "si", "bytes", "percent", "fixed:N"
+    pub x_fmt: Option<NumberFormat>,
+
+    #[argh(option)]
+    /// format of y ticks and values: "plain", "thousands", "si", "bytes", "percent", "fixed:N"
+    pub y_fmt: Option<NumberFormat>,
+
+    #[argh(option)]
+    /// format of the values of a series, eg "memory=bytes" (may be repeated)
+    pub series_fmt: Vec<SeriesSetting<NumberFormat>>,
+
+    #[argh(option, from_str_fn(parse_axis_value))]
+    /// min of the x axis, as a number or a RFC 3339 date
+    pub x_min: Option<i64>,
+
+    #[argh(option, from_str_fn(parse_axis_value))]
+    /// max of the x axis, as a number or a RFC 3339 date
+    pub x_max: Option<i64>,
+
+    #[argh(option)]
+    /// min of the y axis
+    pub y_min: Option<i64>,
+
+    #[argh(option)]
+    /// max of the y axis
+    pub y_max: Option<i64>,
+
+    #[argh(option)]
+    /// whether the y axis includes zero: "always", "never", or "auto" (default)
+    pub zero: Option<ZeroPolicy>,
+
+    #[argh(option)]
+    /// header of a series to draw against a left y axis (may be repeated)
+    pub left: Vec<String>,
+
+    #[argh(switch)]
+    /// put series of much smaller magnitude on a left y axis
+    pub auto_axes: bool,
+
+    #[argh(switch)]
+    /// draw one small panel per series
+    pub facet: bool,
+
+    #[argh(switch)]
+    /// make the facet panels share the same y scale
+    pub shared_y: bool,
+
+    #[argh(option)]
+    /// how series are drawn: "lines" (default), "stacked", "stacked100", or "scatter"
+    pub mode: Option<ChartMode>,
+
+    #[argh(option)]
+    /// value of missing cells in stacks: "zero" (default) or "interpolate"
+    pub stack_missing: Option<MissingPolicy>,
+
+    #[argh(switch)]
+    /// draw a trend line for each series in scatter mode
+    pub trend: bool,
+
+    #[argh(option)]
+    /// how points are joined: "linear" (default), "step-before", "step-after",
+    /// "step-middle", or "monotone", optionally for one series, eg "replicas=step-after"
+    pub interpolation: Vec<SeriesSetting<Interpolation>>,
+
+    #[argh(option)]
+    /// how curves go over missing values: "connect" (default), "break", or "zero",
+    /// optionally for one series, eg "errors=zero"
+    pub gaps: Vec<SeriesSetting<GapPolicy>>,
+
+    #[argh(option, from_str_fn(parse_span))]
+    /// distance between two x beyond which curves are interrupted, eg "10m" or "2d"
+    /// on a time axis
+    pub max_gap: Option<i64>,
+
+    #[argh(option)]
+    /// timezone of displayed dates and of stripes: "utc", "local" (following daylight
+    /// saving changes), or an offset like "+02:00"
+    pub tz: Option<Timezone>,
+
+    #[argh(option)]
+    /// alternating background bands on a time axis: "day" or "week"
+    pub stripes: Option<StripePeriod>,
+
+    #[argh(switch)]
+    /// shade the weekends on a time axis
+    pub weekends: bool,
+
+    #[argh(option)]
+    /// hours to shade on a time axis, eg "20-7"
+    pub night_hours: Option<HourRange>,
+
+    #[argh(option)]
+    /// csv file of events to display, with a time column, and optional "end",
+    /// "label" and "color" columns
+    pub events: Option<PathBuf>,
+
+    #[argh(option)]
+    /// horizontal reference line, as "value[:label[:color]]", eg "200:p99 SLO"
+    /// (may be repeated)
+    pub hline: Vec<HLine>,
+
+    #[argh(switch)]
+    /// extend the y axis so that reference lines are always visible
+    pub hline_in_scale: bool,
+
+    #[argh(option)]
+    /// color of the parts of the curves above the first reference line
+    pub above_color: Option<String>,
+
+    #[argh(switch)]
+    /// write the name of each series at the end of its curve
+    pub direct_labels: bool,
+
+    #[argh(switch)]
+    /// mark the min and max of each series with their values
+    pub extremes: bool,
+
+    #[argh(switch)]
+    /// show min, max, mean, last and p95 of each series in the legend
+    pub legend_stats: bool,
+
+    #[argh(option)]
+    /// write the statistics of the series on stderr: "text" or "json"
+    pub stats: Option<StatsFormat>,
+
+    #[argh(option)]
+    /// write the statistics in this file instead of stderr
+    pub stats_file: Option<PathBuf>,
+
+    #[argh(option)]
+    /// title of the graph, defaulting to the name of the input file
+    pub title: Option<String>,
+
+    #[argh(option)]
+    /// subtitle of the graph
+    pub subtitle: Option<String>,
+
+    #[argh(option)]
+    /// caption of the x axis, defaulting to the header of the first column
+    /// ("" to remove it)
+    pub x_label: Option<String>,
+
+    #[argh(option)]
+    /// caption of the y axis
+    pub y_label: Option<String>,
+}
+
+/// parse a size given as "WIDTHxHEIGHT"
+fn parse_size(s: &str) -> Result<(usize, usize), String> {
+    let err = || format!("expected a size like \"1200x400\", got {:?}", s);
+    let (width, height) = s.split_once(['x', 'X']).ok_or_else(err)?;
+    let width: usize = width.trim().parse().map_err(|_| err())?;
+    let height: usize = height.trim().parse().map_err(|_| err())?;
+    if width < 50 || height < 50 {
+        return Err(format!("size {:?} is too small", s));
+    }
+    Ok((width, height))
+}
+
+/// parse a distance on the x axis, which is either a duration with
+/// a unit (then converted to milliseconds) or a plain number
+fn parse_span(s: &str) -> Result<i64, String> {
+    let idx = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (value, unit) = s.split_at(idx);
+    let value: i64 = value
+        .parse()
+        .map_err(|_| format!("invalid span: {:?}", s))?;
+    let millis = match unit {
+        "" => 1,
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => 60 * 1_000,
+        "h" => 60 * 60 * 1_000,
+        "d" => 24 * 60 * 60 * 1_000,
+        "w" => 7 * 24 * 60 * 60 * 1_000,
+        _ => {
+            return Err(format!("unrecognized unit {:?} in span {:?}", unit, s));
+        }
+    };
+    value
+        .checked_mul(millis)
+        .ok_or_else(|| format!("span too big: {:?}", s))
+}
+
+/// parse a bound of an axis, which may be a date (then converted
+/// to a timestamp in milliseconds) or an integer
+fn parse_axis_value(s: &str) -> Result<i64, String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        Ok(dt.timestamp_millis())
+    } else {
+        s.parse()
+            .map_err(|_| format!("expected a number or a RFC 3339 date, got {:?}", s))
+    }
+}
+
+impl Args {
+    /// build the options of the graph from the launch arguments
+    pub fn graph_options(&self) -> Result<GraphOptions> {
+        let mut options = GraphOptions::default();
+        if let Some((width, height)) = self.size {
+            options.layout.width = width;
+            options.layout.height = height;
+        }
+        if let Some(margins) = self.margins {
+            let layout = &options.layout;
+            if margins.left + margins.right >= layout.width as i64
+                || margins.top + margins.bottom >= layout.height as i64
+            {
+                bail!(
+                    "margins leave no room for the plot in a {}x{} graph",
+                    layout.width,
+                    layout.height,
+                );
+            }
+        }
+        options.layout.margins = self.margins;
+        options.sizing = self.sizing.unwrap_or_default();
+        options.aspect_ratio = self.aspect_ratio.clone();
+        options.theme = self.theme.unwrap_or_default();
+        options.palette = self.palette.unwrap_or_default();
+        options.patterns = self.patterns;
+        if let Some(path) = &self.style_file {
+            options.styles = StyleRule::read_file(path)?;
+        }
+        options.styles.extend(self.style.iter().cloned());
+        options.fonts = self.font.clone();
+        if let Some(path) = &self.embed_font {
+            let family = self
+                .font
+                .iter()
+                .rev()
+                .filter(|setting| setting.role.is_none())
+                .find_map(|setting| setting.family.as_deref())
+                .map(first_family);
+            options.embedded_font = Some(EmbeddedFont::read(path, family)?);
+        }
+        if let Some(x_fmt) = self.x_fmt {
+            options.x_format = x_fmt;
+        }
+        if let Some(y_fmt) = self.y_fmt {
+            options.y_format = y_fmt;
+        }
+        options.series_formats = self.series_fmt.clone();
+        if let (Some(min), Some(max)) = (self.x_min, self.x_max) {
+            if min >= max {
+                bail!("x-min must be less than x-max");
+            }
+        }
+        if let (Some(min), Some(max)) = (self.y_min, self.y_max) {
+            if min >= max {
+                bail!("y-min must be less than y-max");
+            }
+        }
+        options.x_min = self.x_min;
+        options.x_max = self.x_max;
+        options.y_min = self.y_min;
+        options.y_max = self.y_max;
+        if let Some(zero) = self.zero {
+            options.zero = zero;
+        }
+        options.left_axis = self.left.clone();
+        options.auto_axes = self.auto_axes;
+        options.facet = self.facet;
+        options.shared_y = self.shared_y;
+        if let Some(mode) = self.mode {
+            options.mode = mode;
+        }
+        if let Some(stack_missing) = self.stack_missing {
+            options.stack_missing = stack_missing;
+        }
+        options.trend = self.trend;
+        options.interpolations = self.interpolation.clone();
+        options.gaps = self.gaps.clone();
+        options.max_gap = self.max_gap;
+        options.tz = self.tz;
+        options.stripes = self.stripes;
+        options.weekends = self.weekends;
+        options.night_hours = self.night_hours;
+        if let Some(path) = &self.events {
+            options.events = Event::read_file(path)?;
+        }
+        options.hlines = self.hline.clone();
+        options.hlines_in_scale = self.hline_in_scale;
+        options.above_color = self.above_color.clone();
+        options.direct_labels = self.direct_labels;
+        options.extremes = self.extremes;
+        options.legend_stats = self.legend_stats;
+        options.title = self.title.clone().or_else(|| {
+            self.file
+                .as_ref()
+                .and_then(|path| path.file_stem())
+                .map(|stem| stem.to_string_lossy().to_string())
+        });
+        options.subtitle = self.subtitle.clone();
+        options.x_label = self.x_label.clone();
+        options.y_label = self.y_label.clone();
+        if options.above_color.is_some() && options.hlines.is_empty() {
+            bail!("above-color needs a reference line, given with --hline");
+        }
+        if options.facet && options.mode != ChartMode::Lines {
+            bail!("facets are only available in lines mode");
+        }
+        Ok(options)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -30,3 +414,25 @@ impl std::str::FromStr for Format {
         }
     }
 }
+
+#[cfg(test)]
+mod args_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_span() {
+        assert_eq!(parse_span("250"), Ok(250));
+        assert_eq!(parse_span("10m"), Ok(600_000));
+        assert_eq!(parse_span("2d"), Ok(172_800_000));
+        assert!(parse_span("3y").is_err());
+        assert!(parse_span("m").is_err());
+        assert!(parse_span("9999999999999999d").is_err());
+    }
+    #[test]
+    fn test_margins_too_big() {
+        let options = |args: &[&str]| Args::from_args(&["csv2svg"], args).unwrap().graph_options();
+        assert!(options(&["--size", "100x100", "--margins", "60"]).is_err());
+        assert!(options(&["--size", "300x200", "--margins", "10,200,10,200"]).is_err());
+        assert!(options(&["--size", "300x200", "--margins", "10,100,10,100"]).is_ok());
+    }
+}
@@ -5,37 +5,191 @@ pub use args::*;
 use {
     crate::*,
     anyhow::*,
+    argh::FromArgs,
     crossterm::tty::IsTty,
     std::{
         fs::File,
         io::{self, stdout, Write},
         path::PathBuf,
     },
+    svg::Document,
 };
 
-pub fn run() -> Result<()> {
-    let args: Args = argh::from_env();
-    debug!("args: {:#?}", &args);
-    if args.version {
-        println!("csv2svg {}", env!("CARGO_PKG_VERSION"));
-        return Ok(());
+/// whether the argument is `--no-<switch>`, which isn't given to argh
+/// but cancels the switch set in a config file
+fn is_negated_switch(arg: &str) -> bool {
+    arg.strip_prefix("--no-").is_some_and(|name| {
+        let switch = format!("--{}", name);
+        // only switches are valid without a value
+        Args::from_args(&["csv2svg"], &[&switch]).is_ok()
+    })
+}
+
+/// the name of the option given by the argument, without the `no-`
+/// of a negated switch
+fn option_name(arg: &str) -> Option<&str> {
+    if arg == "-f" {
+        return Some("format");
+    }
+    let name = arg.strip_prefix("--")?;
+    if is_negated_switch(arg) {
+        return Some(&name[3..]);
+    }
+    Some(name)
+}
+
+/// parse the arguments of the command line, or exit on error
+/// or on --help
+fn parse_args(cmd: &str, args: &[String], source: Option<&PathBuf>) -> Args {
+    let args: Vec<&str> = args
+        .iter()
+        .map(String::as_str)
+        .filter(|arg| !is_negated_switch(arg))
+        .collect();
+    Args::from_args(&[cmd], &args).unwrap_or_else(|early_exit| {
+        if early_exit.status.is_ok() {
+            println!("{}", early_exit.output);
+            std::process::exit(0);
+        }
+        eprintln!("{}", early_exit.output);
+        if let Some(source) = source {
+            eprintln!("Check the file {:?}", source);
+        }
+        eprintln!("Run {} --help for more information.", cmd);
+        std::process::exit(1);
+    })
+}
+
+/// merge two lists of arguments, the options of `base` which are
+/// also given, or negated, in `overrides` being dropped
+fn merge_args(base: Vec<String>, overrides: &[String]) -> Vec<String> {
+    let given: Vec<&str> = overrides
+        .iter()
+        .filter_map(|arg| option_name(arg))
+        .collect();
+    // options are pairs of a name and optional values
+    let mut merged = Vec::new();
+    let mut keep = true;
+    for arg in base {
+        if let Some(name) = option_name(&arg) {
+            keep = !given.contains(&name);
+        }
+        if keep {
+            merged.push(arg);
+        }
     }
-    let raw_tbl = RawTbl::read(io::stdin())?;
+    merged.extend(overrides.iter().cloned());
+    merged
+}
+
+/// read the arguments of the command line, completed with the
+/// ones of the config file which aren't already given. Return the
+/// name of the command and the merged arguments too
+fn read_args() -> Result<(String, Vec<String>, Args)> {
+    let strings: Vec<String> = std::env::args().collect();
+    let cmd = strings.first().map_or("csv2svg", String::as_str);
+    let cmd = cmd
+        .rsplit(std::path::MAIN_SEPARATOR)
+        .next()
+        .unwrap_or(cmd)
+        .to_string();
+    let cli_args = strings[1.min(strings.len())..].to_vec();
+    let args = parse_args(&cmd, &cli_args, None);
+    let config = match Config::read()? {
+        Some(config) => config,
+        None => {
+            if let Some(preset) = &args.preset {
+                bail!(
+                    "no preset {:?}: there's no config file at {:?}",
+                    preset,
+                    Config::path()?
+                );
+            }
+            return Ok((cmd, cli_args, args));
+        }
+    };
+    let config_args = config.args(args.preset.as_deref())?;
+    let merged = merge_args(config_args, &cli_args);
+    if merged.len() == cli_args.len() {
+        return Ok((cmd, cli_args, args));
+    }
+    debug!("args with config: {:?}", &merged);
+    let args = parse_args(&cmd, &merged, Some(&Config::path()?));
+    Ok((cmd, merged, args))
+}
+
+/// read the table from a csv file, or from stdin
+fn read_tbl(path: Option<&PathBuf>) -> Result<Tbl> {
+    let raw_tbl = match path {
+        Some(path) => {
+            let file = File::open(path).with_context(|| format!("can't open {:?}", path))?;
+            RawTbl::read(file)?
+        }
+        None => RawTbl::read(io::stdin())?,
+    };
     //debug!("raw tbl: {:#?}", &raw_tbl);
     let tbl = Tbl::from_raw(raw_tbl)?;
     //debug!("tbl: {:#?}", &tbl);
     debug!("tbl dim: {:?}", tbl.dim());
-    let graph = Graph::new(tbl);
+    Ok(tbl)
+}
+
+fn build_graph(tbl: Tbl, options: GraphOptions) -> Result<Graph> {
+    if options.mode != ChartMode::Scatter && !tbl.x_seq().is_full_and_increasing() {
+        bail!("the first column must be full and increasing, unless you use --mode scatter");
+    }
+    for header in &options.left_axis {
+        if !tbl.y_seqs().any(|seq| &seq.header == header) {
+            let headers: Vec<&str> = tbl.y_seqs().map(|seq| seq.header.as_str()).collect();
+            bail!(
+                "no series {:?} for --left, the series are {:?}",
+                header,
+                headers
+            );
+        }
+    }
+    if (options.direct_labels || options.extremes) && options.mode != ChartMode::Lines {
+        bail!("--direct-labels and --extremes are only supported in lines mode");
+    }
+    let has_bands = options.stripes.is_some() || options.weekends || options.night_hours.is_some();
+    if has_bands && !matches!(tbl.x_seq().nature, Nature::Date(_)) {
+        warn!("stripes, weekends and night hours are only drawn on a time axis");
+    }
+    Ok(Graph::new(tbl, options))
+}
+
+/// build the SVG of the graph and, in auto theme, a light variant
+/// which the HTML page displays when the user prefers light colors
+fn build_svgs(graph: Graph, theme: ThemeName) -> (Document, Option<Document>) {
     let svg = graph.build_svg();
+    let light_svg = if theme == ThemeName::Auto {
+        // both variants are in the same page, their ids must differ
+        let mut graph = graph;
+        let prefix = format!("{}light-", graph.id_prefix());
+        graph.set_id_prefix(prefix);
+        graph.set_theme(&LIGHT_THEME);
+        Some(graph.build_svg())
+    } else {
+        None
+    };
+    (svg, light_svg)
+}
+
+/// write the SVG or the HTML page, on stdout when it's piped, or in
+/// a temp file opened by the system
+fn write_output<F>(format: Option<Format>, svg: Option<&Document>, write_html: F) -> Result<()>
+where
+    F: Fn(&mut dyn Write) -> Result<()>,
+{
     if is_output_piped() {
         // when the output is piped, the default format is svg
         let mut w = io::stdout();
-        match args.format {
-            Some(Format::Html) => {
-                html::write_embedded(&mut w, &svg)?;
+        match (format, svg) {
+            (Some(Format::Html), _) | (_, None) => {
+                write_html(&mut w)?;
             }
-            _ => {
-                svg::write(&mut w, &svg)?;
+            (_, Some(svg)) => {
+                svg::write(&mut w, svg)?;
             }
         }
         w.write_all(b"\n")?;
@@ -45,12 +199,12 @@ pub fn run() -> Result<()> {
         // As it's the most expressive format, we prefer to
         // open some HTML in a browser
         let (mut w, path) = temp_file()?;
-        match args.format {
-            Some(Format::Svg) => {
-                svg::write(&mut w, &svg)?;
+        match (format, svg) {
+            (Some(Format::Svg), Some(svg)) => {
+                svg::write(&mut w, svg)?;
             }
             _ => {
-                html::write_embedded(&mut w, &svg)?;
+                write_html(&mut w)?;
             }
         }
         open::that(path)?;
@@ -58,6 +212,116 @@ pub fn run() -> Result<()> {
     Ok(())
 }
 
+pub fn run() -> Result<()> {
+    let (cmd, arg_strings, args) = read_args()?;
+    debug!("args: {:#?}", &args);
+    if args.version {
+        println!("csv2svg {}", env!("CARGO_PKG_VERSION"));
+        return Ok(());
+    }
+    if args.dashboard.is_some() || !args.chart.is_empty() {
+        return run_dashboard(&cmd, arg_strings, &args);
+    }
+    let tbl = read_tbl(args.file.as_ref())?;
+    let graph = build_graph(tbl, args.graph_options()?)?;
+    if args.stats.is_some() || args.stats_file.is_some() {
+        let format = args.stats.unwrap_or(StatsFormat::Text);
+        let stats = graph.stats();
+        match &args.stats_file {
+            Some(path) => {
+                let file = File::create(path)
+                    .with_context(|| format!("can't create stats file {:?}", path))?;
+                write_stats(file, &stats, format)?;
+            }
+            None => {
+                write_stats(io::stderr(), &stats, format)?;
+            }
+        }
+    }
+    let theme = args.theme.unwrap_or_default();
+    let (svg, light_svg) = build_svgs(graph, theme);
+    let theme = theme.theme();
+    write_output(args.format, Some(&svg), |w| match &light_svg {
+        Some(light_svg) => html::write_embedded_auto(w, &svg, light_svg),
+        None => html::write_embedded(w, &svg, theme),
+    })
+}
+
+/// build the graphs of the dashboard, each one with the arguments of
+/// the dashboard file, then the ones of the command line, then the
+/// ones specific to the graph, and write them in a HTML page
+fn run_dashboard(cmd: &str, arg_strings: Vec<String>, args: &Args) -> Result<()> {
+    if args.file.is_some() {
+        bail!("the csv files of a dashboard must be given with --chart or in the dashboard file");
+    }
+    if args.format == Some(Format::Svg) {
+        bail!("a dashboard can only be written in HTML");
+    }
+    if args.stats.is_some() || args.stats_file.is_some() {
+        warn!("statistics aren't computed for dashboards");
+    }
+    let layout = match &args.dashboard {
+        Some(path) => DashboardLayout::read(path)?,
+        None => DashboardLayout::default(),
+    };
+    let mut charts = layout.charts;
+    charts.extend(args.chart.iter().map(|file| ChartSpec {
+        file: file.clone(),
+        args: Vec::new(),
+    }));
+    // the arguments of the command line, without the ones defining
+    // the dashboard, which all take a value
+    let mut common_args = Vec::new();
+    let mut strings = arg_strings.into_iter();
+    while let Some(arg) = strings.next() {
+        if matches!(arg.as_str(), "--chart" | "--dashboard" | "--columns") {
+            strings.next();
+        } else {
+            common_args.push(arg);
+        }
+    }
+    let common_args = merge_args(layout.args, &common_args);
+    let mut tbls = Vec::new();
+    let mut all_options = Vec::new();
+    for chart in &charts {
+        let mut chart_args = merge_args(common_args.clone(), &chart.args);
+        chart_args.push(chart.file.to_string_lossy().to_string());
+        let chart_args = parse_args(cmd, &chart_args, args.dashboard.as_ref());
+        tbls.push(read_tbl(Some(&chart.file))?);
+        all_options.push(chart_args.graph_options()?);
+    }
+    // graphs share their x range, unless they set it
+    if let Some((x_min, x_max)) = shared_x_range(&tbls) {
+        for options in &mut all_options {
+            options.x_min = options.x_min.or(Some(x_min));
+            options.x_max = options.x_max.or(Some(x_max));
+        }
+    }
+    // the page has only one background
+    let theme = all_options
+        .first()
+        .map(|options| options.theme)
+        .unwrap_or_default();
+    if all_options.iter().any(|options| options.theme != theme) {
+        bail!("the graphs of a dashboard must all have the same theme");
+    }
+    let mut graphs = Vec::new();
+    for (chart_idx, (tbl, options)) in tbls.into_iter().zip(all_options).enumerate() {
+        let theme = options.theme;
+        let mut graph = build_graph(tbl, options)?;
+        graph.set_id_prefix(chart_id_prefix(chart_idx));
+        graphs.push(build_svgs(graph, theme));
+    }
+    let columns = args
+        .columns
+        .or(layout.columns)
+        .unwrap_or(2)
+        .clamp(1, graphs.len().max(1));
+    write_output(args.format, None, |w| {
+        html::write_dashboard(w, &graphs, columns, theme)
+    })
+}
+
 fn is_output_piped() -> bool {
     !stdout().is_tty()
 }
@@ -69,5 +333,33 @@ pub fn temp_file() -> io::Result<(File, PathBuf)> {
         .rand_bytes(12)
         .tempfile()?
         .keep()
-        .map_err(|_| io::Error::new(io::ErrorKind::Other, "temp file can't be kept"))
+        .map_err(|_| io::Error::other("temp file can't be kept"))
+}
+
+#[cfg(test)]
+mod cli_tests {
+    use super::*;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_negated_switches() {
+        // a switch of the config is cancelled on the command line
+        let config =
+            Config::parse("legend-stats = true\nweekends = true\ntitle = \"Load\"").unwrap();
+        let merged = merge_args(config.args(None).unwrap(), &strings(&["--no-weekends"]));
+        let args = parse_args("csv2svg", &merged, None);
+        assert!(args.legend_stats);
+        assert!(!args.weekends);
+        // and a false value of a chart cancels the common one
+        let common = strings(&["--weekends", "--title", "Load"]);
+        let chart = entries_args(vec![("weekends".to_string(), ConfigValue::Bool(false))]);
+        let args = parse_args("csv2svg", &merge_args(common, &chart), None);
+        assert!(!args.weekends);
+        assert_eq!(args.title.as_deref(), Some("Load"));
+        assert!(!is_negated_switch("--no-title"));
+        assert!(!is_negated_switch("--no-whatever"));
+    }
 }
@@ -0,0 +1,178 @@
+use svg::node::element::path::Data;
+
+/// how the points of a series are joined
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Interpolation {
+    /// straight segments
+    #[default]
+    Linear,
+    /// the value changes at the previous point
+    StepBefore,
+    /// the value changes at the next point
+    StepAfter,
+    /// the value changes halfway between points
+    StepMiddle,
+    /// a smooth curve never overshooting the data
+    Monotone,
+}
+
+impl std::str::FromStr for Interpolation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_ref() {
+            "linear" => Ok(Self::Linear),
+            "step-before" => Ok(Self::StepBefore),
+            "step-after" | "step" => Ok(Self::StepAfter),
+            "step-middle" => Ok(Self::StepMiddle),
+            "monotone" | "smooth" => Ok(Self::Monotone),
+            _ => Err(format!("unrecognized interpolation {:?}", s)),
+        }
+    }
+}
+
+/// how a curve goes over a missing value
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GapPolicy {
+    /// join the points before and after the gap
+    #[default]
+    Connect,
+    /// interrupt the curve
+    Break,
+    /// consider the missing value as zero
+    Zero,
+}
+
+impl std::str::FromStr for GapPolicy {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_ref() {
+            "connect" => Ok(Self::Connect),
+            "break" => Ok(Self::Break),
+            "zero" => Ok(Self::Zero),
+            _ => Err(format!("unrecognized gap policy {:?}", s)),
+        }
+    }
+}
+
+/// add to the path data a curve going through all points, which
+/// are assumed to be ordered by x
+pub fn append_curve(mut data: Data, points: &[(i64, i64)], interpolation: Interpolation) -> Data {
+    if points.is_empty() {
+        return data;
+    }
+    data = data.move_to(points[0]);
+    match interpolation {
+        Interpolation::Linear => {
+            for &p in &points[1..] {
+                data = data.line_to(p);
+            }
+        }
+        Interpolation::StepBefore => {
+            for &(x, y) in &points[1..] {
+                data = data.vertical_line_to(y).horizontal_line_to(x);
+            }
+        }
+        Interpolation::StepAfter => {
+            for &(x, y) in &points[1..] {
+                data = data.horizontal_line_to(x).vertical_line_to(y);
+            }
+        }
+        Interpolation::StepMiddle => {
+            for idx in 1..points.len() {
+                let (x0, _) = points[idx - 1];
+                let (x, y) = points[idx];
+                data = data
+                    .horizontal_line_to((x0 + x) as f64 / 2.0)
+                    .vertical_line_to(y)
+                    .horizontal_line_to(x);
+            }
+        }
+        Interpolation::Monotone => {
+            let tangents = monotone_tangents(points);
+            for idx in 1..points.len() {
+                let (x0, y0) = (points[idx - 1].0 as f64, points[idx - 1].1 as f64);
+                let (x1, y1) = (points[idx].0 as f64, points[idx].1 as f64);
+                let h = (x1 - x0) / 3.0;
+                data = data.cubic_curve_to((
+                    round(x0 + h),
+                    round(y0 + tangents[idx - 1] * h),
+                    round(x1 - h),
+                    round(y1 - tangents[idx] * h),
+                    x1,
+                    y1,
+                ));
+            }
+        }
+    }
+    data
+}
+
+fn round(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+/// compute the tangents at all points of a monotone cubic hermite
+/// interpolation (Steffen's method), which ensures the curve never
+/// goes beyond the values of the points surrounding a segment
+fn monotone_tangents(points: &[(i64, i64)]) -> Vec<f64> {
+    let n = points.len();
+    let secants: Vec<f64> = points
+        .windows(2)
+        .map(|w| {
+            let h = (w[1].0 - w[0].0) as f64;
+            if h == 0.0 {
+                0.0
+            } else {
+                (w[1].1 - w[0].1) as f64 / h
+            }
+        })
+        .collect();
+    let mut tangents = vec![0.0; n];
+    if n < 2 {
+        return tangents;
+    }
+    tangents[0] = secants[0];
+    tangents[n - 1] = secants[n - 2];
+    for idx in 1..n - 1 {
+        let (s0, s1) = (secants[idx - 1], secants[idx]);
+        let h0 = (points[idx].0 - points[idx - 1].0) as f64;
+        let h1 = (points[idx + 1].0 - points[idx].0) as f64;
+        if h0 + h1 == 0.0 {
+            continue;
+        }
+        let p = (s0 * h1 + s1 * h0) / (h0 + h1);
+        tangents[idx] = (s0.signum() + s1.signum()) * s0.abs().min(s1.abs()).min(0.5 * p.abs());
+    }
+    tangents
+}
+
+#[cfg(test)]
+mod curve_tests {
+    use super::*;
+
+    fn path(points: &[(i64, i64)], interpolation: Interpolation) -> String {
+        let data = append_curve(Data::new(), points, interpolation);
+        svg::node::Value::from(data).to_string()
+    }
+
+    #[test]
+    fn test_tangents_at_extremum() {
+        // the tangent must be flat at a local maximum, so that
+        // the curve doesn't go above it
+        let tangents = monotone_tangents(&[(0, 0), (10, 50), (20, 0)]);
+        assert_eq!(tangents, vec![5.0, 0.0, -5.0]);
+    }
+    #[test]
+    fn test_tangents_of_line() {
+        let tangents = monotone_tangents(&[(0, 0), (10, 10), (30, 30)]);
+        assert_eq!(tangents, vec![1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_steps() {
+        let points = [(0, 0), (10, 5)];
+        assert_eq!(path(&points, Interpolation::StepAfter), "M0,0 H10 V5");
+        assert_eq!(path(&points, Interpolation::StepBefore), "M0,0 V5 H10");
+        assert_eq!(path(&points, Interpolation::StepMiddle), "M0,0 H5 V5 H10");
+    }
+}
@@ -0,0 +1,159 @@
+use {
+    crate::*,
+    anyhow::{anyhow, bail, Context, Result},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// a graph of a dashboard: the csv file, and the arguments
+/// specific to this graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartSpec {
+    pub file: PathBuf,
+    pub args: Vec<String>,
+}
+
+/// the content of a dashboard file, eg
+///
+/// ```toml
+/// columns = 2
+/// y-min = 0
+///
+/// [charts.cpu]
+/// file = "cpu.csv"
+/// title = "CPU"
+/// y-fmt = "percent"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DashboardLayout {
+    pub columns: Option<usize>,
+    /// the arguments common to all graphs
+    pub args: Vec<String>,
+    pub charts: Vec<ChartSpec>,
+}
+
+impl DashboardLayout {
+    /// parse a dashboard file, relative paths of csv files being
+    /// resolved against `dir`
+    pub fn parse(content: &str, dir: &Path) -> Result<Self> {
+        let (mut entries, tables) = parse_tables(content, &["charts", "chart"])?;
+        let mut layout = Self::default();
+        if let Some(idx) = entries.iter().position(|(key, _)| key == "columns") {
+            layout.columns = match entries.remove(idx).1 {
+                ConfigValue::Text(text) => text.parse().ok().filter(|&c| c > 0),
+                _ => None,
+            };
+            if layout.columns.is_none() {
+                bail!("columns must be a positive integer");
+            }
+        }
+        layout.args = entries_args(entries);
+        for (name, mut entries) in tables {
+            let idx = entries
+                .iter()
+                .position(|(key, _)| key == "file")
+                .ok_or_else(|| anyhow!("no file for chart {:?}", name))?;
+            let file = match entries.remove(idx).1 {
+                ConfigValue::Text(file) => dir.join(file),
+                _ => bail!("the file of chart {:?} must be a string", name),
+            };
+            layout.charts.push(ChartSpec {
+                file,
+                args: entries_args(entries),
+            });
+        }
+        Ok(layout)
+    }
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("can't read dashboard file {:?}", path))?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        Self::parse(&content, dir).with_context(|| format!("invalid dashboard file {:?}", path))
+    }
+}
+
+/// the prefix of the ids of a graph of the dashboard, as all graphs
+/// are in the same page
+pub fn chart_id_prefix(chart_idx: usize) -> String {
+    format!("chart-{}-", chart_idx)
+}
+
+/// the range of x covering all tables, when their x are of the same
+/// nature, so that the graphs of a dashboard can be aligned
+pub fn shared_x_range(tbls: &[Tbl]) -> Option<(i64, i64)> {
+    let first = tbls.first()?.x_seq();
+    let same_nature = tbls.iter().all(|tbl| {
+        matches!(
+            (&first.nature, &tbl.x_seq().nature),
+            (Nature::Date(_), Nature::Date(_)) | (Nature::Integer, Nature::Integer)
+        )
+    });
+    if !same_nature {
+        return None;
+    }
+    let min = tbls.iter().map(|tbl| tbl.x_seq().min).min()?;
+    let max = tbls.iter().map(|tbl| tbl.x_seq().max).max()?;
+    Some((min, max))
+}
+
+#[cfg(test)]
+mod dashboard_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dashboard() {
+        let layout = DashboardLayout::parse(
+            r#"
+columns = 3
+y-min = 0
+
+[charts.cpu]
+file = "cpu.csv"
+title = "CPU"
+
+[charts.latency]
+file = "/var/latency.csv"
+"#,
+            Path::new("/data"),
+        )
+        .unwrap();
+        assert_eq!(layout.columns, Some(3));
+        assert_eq!(layout.args, ["--y-min", "0"]);
+        assert_eq!(layout.charts.len(), 2);
+        assert_eq!(layout.charts[0].file, Path::new("/data/cpu.csv"));
+        assert_eq!(layout.charts[0].args, ["--title", "CPU"]);
+        assert_eq!(layout.charts[1].file, Path::new("/var/latency.csv"));
+        assert!(DashboardLayout::parse("[charts.cpu]\ntitle = \"CPU\"", Path::new("")).is_err());
+        assert!(DashboardLayout::parse("columns = 0", Path::new("")).is_err());
+        assert!(DashboardLayout::parse("[panels.cpu]", Path::new("")).is_err());
+    }
+    #[test]
+    fn test_unique_ids() {
+        let csvs = ["x,a\n1,10\n2,20\n3,30\n", "x,a,b\n1,1,\n5,2,7\n9,3,9\n"];
+        let mut ids = Vec::new();
+        for (chart_idx, csv) in csvs.iter().enumerate() {
+            let tbl = Tbl::from_raw(RawTbl::read(csv.as_bytes()).unwrap()).unwrap();
+            let options = GraphOptions {
+                hlines: vec!["2".parse().unwrap()],
+                above_color: Some("red".to_string()),
+                ..GraphOptions::default()
+            };
+            let mut graph = Graph::new(tbl, options);
+            graph.set_id_prefix(chart_id_prefix(chart_idx));
+            let svg = graph.build_svg().to_string();
+            for part in svg.split(" id=\"").skip(1) {
+                let id = part.split('"').next().unwrap().to_string();
+                assert!(svg.contains(&format!("url(#{})", id)));
+                ids.push(id);
+            }
+        }
+        assert_eq!(ids.len(), 6);
+        let count = ids.len();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), count);
+    }
+}
@@ -0,0 +1,110 @@
+use {
+    crate::*,
+    anyhow::{anyhow, bail, Context, Result},
+    chrono::DateTime,
+    std::{fs::File, io::Read, path::Path},
+};
+
+/// something which happened at a time, or during a time range,
+/// and which is displayed over the curves
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub start: i64,
+    pub end: Option<i64>,
+    pub label: String,
+    pub color: Option<String>,
+}
+
+/// parse a time of an event, either a RFC 3339 date (converted to
+/// a timestamp in milliseconds) or an integer
+fn parse_time(s: &str) -> Option<i64> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        Some(dt.timestamp_millis())
+    } else {
+        s.parse().ok()
+    }
+}
+
+impl Event {
+    /// read events from a csv file whose first column is the time.
+    /// The other columns are recognized by their header: "end",
+    /// "label" and "color" (when there's no "label" column, the
+    /// first unrecognized column is used)
+    pub fn read<R: Read>(r: R) -> Result<Vec<Self>> {
+        let raw_tbl = RawTbl::read(r)?;
+        let header_idx = |names: &[&str]| {
+            raw_tbl
+                .cols
+                .iter()
+                .skip(1)
+                .position(|col| names.contains(&col.header.trim().to_lowercase().as_ref()))
+                .map(|idx| idx + 1)
+        };
+        let end_idx = header_idx(&["end", "end time", "end_time", "until"]);
+        let color_idx = header_idx(&["color", "colour"]);
+        let label_idx = header_idx(&["label", "name", "title"]).or_else(|| {
+            (1..raw_tbl.cols.len()).find(|&idx| Some(idx) != end_idx && Some(idx) != color_idx)
+        });
+        let cell = |col_idx: Option<usize>, row_idx: usize| -> Option<&str> {
+            col_idx.and_then(|col_idx| raw_tbl.cols[col_idx].cells[row_idx].as_deref())
+        };
+        let mut events = Vec::new();
+        for row_idx in 0..raw_tbl.row_count() {
+            let start = match cell(Some(0), row_idx) {
+                Some(s) => parse_time(s).ok_or_else(|| anyhow!("invalid event time: {:?}", s))?,
+                None => {
+                    continue;
+                }
+            };
+            let end = match cell(end_idx, row_idx) {
+                Some(s) => {
+                    Some(parse_time(s).ok_or_else(|| anyhow!("invalid event end time: {:?}", s))?)
+                }
+                None => None,
+            };
+            if let Some(end) = end {
+                if end < start {
+                    bail!("an event ends before it starts");
+                }
+            }
+            events.push(Self {
+                start,
+                end,
+                label: cell(label_idx, row_idx).unwrap_or_default().to_string(),
+                color: cell(color_idx, row_idx).map(|s| s.to_string()),
+            });
+        }
+        events.sort_by_key(|e| e.start);
+        Ok(events)
+    }
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Vec<Self>> {
+        let path = path.as_ref();
+        let file =
+            File::open(path).with_context(|| format!("can't open events file {:?}", path))?;
+        Self::read(file)
+    }
+    /// whether some part of the event is in the [min, max] range
+    pub fn overlaps(&self, min: i64, max: i64) -> bool {
+        self.start <= max && self.end.unwrap_or(self.start) >= min
+    }
+}
+
+#[cfg(test)]
+mod event_tests {
+    use super::*;
+
+    #[test]
+    fn test_read_events() {
+        let csv = "time,color,what,end\n\
+            20,,crash,\n\
+            10,#f00,deploy,15\n";
+        let events = Event::read(csv.as_bytes()).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].start, 10);
+        assert_eq!(events[0].end, Some(15));
+        assert_eq!(events[0].label, "deploy");
+        assert_eq!(events[0].color.as_deref(), Some("#f00"));
+        assert_eq!(events[1].label, "crash");
+        assert_eq!(events[1].end, None);
+    }
+}
@@ -0,0 +1,265 @@
+use {
+    anyhow::{bail, Context, Result},
+    base64::Engine,
+    std::{fs, path::Path},
+};
+
+/// the fonts used when none is specified, Arial and Helvetica having
+/// the metrics used to estimate the widths of texts
+pub static DEFAULT_FONT_FAMILY: &str = "Arial, Helvetica, sans-serif";
+
+/// the kind of a text of the graph, each one having its own font
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextRole {
+    Title,
+    Subtitle,
+    /// the captions of the axes
+    Caption,
+    /// the legend, the panel titles and the direct labels
+    Legend,
+    /// the values along the axes
+    Tick,
+    /// the labels of the hovered points, of the events, of the
+    /// reference lines and of the extremes
+    Label,
+}
+
+impl TextRole {
+    /// the font size, before scaling to the size of the graph
+    pub fn default_size(self) -> f64 {
+        match self {
+            Self::Title => 15.0,
+            Self::Subtitle | Self::Caption | Self::Legend => 10.0,
+            Self::Tick | Self::Label => 8.0,
+        }
+    }
+}
+
+impl std::str::FromStr for TextRole {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_ref() {
+            "title" => Ok(Self::Title),
+            "subtitle" => Ok(Self::Subtitle),
+            "caption" | "captions" => Ok(Self::Caption),
+            "legend" => Ok(Self::Legend),
+            "tick" | "ticks" => Ok(Self::Tick),
+            "label" | "labels" => Ok(Self::Label),
+            _ => Err(format!("unrecognized text role {:?}", s)),
+        }
+    }
+}
+
+/// a font family and/or size, applying to a role or to all of them
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontSetting {
+    /// the role, none for all roles
+    pub role: Option<TextRole>,
+    pub family: Option<String>,
+    pub size: Option<f64>,
+}
+
+impl FontSetting {
+    pub fn applies_to(&self, role: TextRole) -> bool {
+        self.role.is_none_or(|r| r == role)
+    }
+}
+
+impl std::str::FromStr for FontSetting {
+    type Err = String;
+    /// parse `role:family=...,size=...`, where the role may be `all`.
+    /// As families are often lists, a part without `=` continues the
+    /// previous value
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (role, settings) = s
+            .split_once(':')
+            .ok_or_else(|| format!("expected a font like \"title:size=18\", got {:?}", s))?;
+        let role = match role.trim() {
+            "all" | "*" => None,
+            role => Some(role.parse()?),
+        };
+        let mut entries: Vec<(String, String)> = Vec::new();
+        for part in settings.split(',') {
+            match (part.split_once('='), entries.last_mut()) {
+                (Some((key, value)), _) => {
+                    entries.push((key.trim().to_lowercase(), value.trim().to_string()));
+                }
+                (None, Some((_, value))) => {
+                    value.push_str(", ");
+                    value.push_str(part.trim());
+                }
+                (None, None) => {
+                    return Err(format!("expected key=value, got {:?}", part));
+                }
+            }
+        }
+        let mut setting = Self {
+            role,
+            family: None,
+            size: None,
+        };
+        for (key, value) in entries {
+            match key.as_ref() {
+                "family" => {
+                    setting.family = Some(value);
+                }
+                "size" => {
+                    setting.size = Some(
+                        value
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|size| *size > 0.0)
+                            .ok_or_else(|| format!("invalid font size: {:?}", value))?,
+                    );
+                }
+                _ => {
+                    return Err(format!("unknown font property {:?}", key));
+                }
+            }
+        }
+        Ok(setting)
+    }
+}
+
+/// the family of the texts of a role, if one was set, the later settings
+/// having precedence
+pub fn role_font_family(settings: &[FontSetting], role: TextRole) -> Option<&str> {
+    settings
+        .iter()
+        .rev()
+        .filter(|setting| setting.applies_to(role))
+        .find_map(|setting| setting.family.as_deref())
+}
+
+/// the unscaled font size of the texts of a role
+pub fn role_font_size(settings: &[FontSetting], role: TextRole) -> f64 {
+    settings
+        .iter()
+        .rev()
+        .filter(|setting| setting.applies_to(role))
+        .find_map(|setting| setting.size)
+        .unwrap_or_else(|| role.default_size())
+}
+
+/// the first name of a list of font families, without its quotes,
+/// eg `Inter` for `'Inter', sans-serif`
+pub fn first_family(families: &str) -> &str {
+    families
+        .split(',')
+        .next()
+        .unwrap_or(families)
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'')
+}
+
+/// a font file embedded in the SVG, so that it renders the same
+/// where the font isn't installed. The file isn't subsetted, it's
+/// up to the user to give a small one
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedFont {
+    /// the name under which the font is declared
+    pub family: String,
+    /// the format, as written in the `src` of the `@font-face`
+    pub format: &'static str,
+    pub data: Vec<u8>,
+}
+
+impl EmbeddedFont {
+    /// read a woff2, woff, ttf or otf file. The family is the
+    /// given one or, by default, the name of the file
+    pub fn read<P: AsRef<Path>>(path: P, family: Option<&str>) -> Result<Self> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+        let format = match extension.as_deref() {
+            Some("woff2") => "woff2",
+            Some("woff") => "woff",
+            Some("ttf") => "truetype",
+            Some("otf") => "opentype",
+            _ => bail!(
+                "unsupported font file {:?}: expected woff2, woff, ttf or otf",
+                path
+            ),
+        };
+        let family = match family {
+            Some(family) => family.to_string(),
+            None => path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("embedded")
+                .to_string(),
+        };
+        // the family is written between quotes in the CSS
+        if family.is_empty() || family.contains(['"', '\'', '\\']) {
+            bail!(
+                "invalid font family {:?}: it can't contain quotes or backslashes",
+                family
+            );
+        }
+        let data = fs::read(path).with_context(|| format!("can't read font file {:?}", path))?;
+        Ok(Self {
+            family,
+            format,
+            data,
+        })
+    }
+    /// the `@font-face` rule declaring the font
+    pub fn css(&self) -> String {
+        format!(
+            "@font-face {{ font-family: \"{}\"; src: url(data:font/{};base64,{}) format(\"{}\"); }}\n",
+            self.family,
+            match self.format {
+                "truetype" => "ttf",
+                "opentype" => "otf",
+                format => format,
+            },
+            base64::engine::general_purpose::STANDARD.encode(&self.data),
+            self.format,
+        )
+    }
+}
+
+#[cfg(test)]
+mod font_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_font_setting() {
+        let setting: FontSetting = "title:family=Georgia, serif,size=18".parse().unwrap();
+        assert_eq!(setting.role, Some(TextRole::Title));
+        assert_eq!(setting.family.as_deref(), Some("Georgia, serif"));
+        assert_eq!(setting.size, Some(18.0));
+        let setting: FontSetting = "all:size=9".parse().unwrap();
+        assert_eq!(setting.role, None);
+        assert!("title".parse::<FontSetting>().is_err());
+        assert!("title:size=big".parse::<FontSetting>().is_err());
+        assert!("footer:size=8".parse::<FontSetting>().is_err());
+    }
+    #[test]
+    fn test_font_precedence() {
+        let settings: Vec<FontSetting> = vec![
+            "all:family=Inter,size=9".parse().unwrap(),
+            "tick:family=Menlo".parse().unwrap(),
+        ];
+        assert_eq!(role_font_family(&settings, TextRole::Tick), Some("Menlo"));
+        assert_eq!(role_font_family(&settings, TextRole::Title), Some("Inter"));
+        assert_eq!(role_font_size(&settings, TextRole::Tick), 9.0);
+        assert_eq!(role_font_size(&[], TextRole::Title), 15.0);
+    }
+    #[test]
+    fn test_first_family() {
+        assert_eq!(first_family("Inter"), "Inter");
+        assert_eq!(first_family("'Inter', sans-serif"), "Inter");
+        assert_eq!(first_family(" \"Fira Sans\",serif"), "Fira Sans");
+    }
+    #[test]
+    fn test_quoted_embedded_family() {
+        let path = std::env::temp_dir().join("csv2svg-test-font.woff2");
+        fs::write(&path, [0u8; 4]).unwrap();
+        assert!(EmbeddedFont::read(&path, Some("Inter")).is_ok());
+        assert!(EmbeddedFont::read(&path, Some("In\"ter")).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
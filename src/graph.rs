@@ -8,94 +8,1001 @@ use {
     },
 };
 
+/// the height of a line of the legend, when it's a table
+static LEGEND_LINE_HEIGHT: i64 = 12;
+
+/// the height of the title, when there's one
+static TITLE_HEIGHT: i64 = 22;
+
+/// the height of the subtitle, when there's one
+static SUBTITLE_HEIGHT: i64 = 14;
+
+/// the room taken by an axis caption
+static CAPTION_HEIGHT: i64 = 14;
+
+/// the room taken by the title of a facet
+static FACET_TITLE_HEIGHT: i64 = 14;
+
+/// the minimal size of the plot of a facet, under which the graph
+/// gets more rows, and taller
+static FACET_MIN_WIDTH: i64 = 100;
+static FACET_MIN_HEIGHT: i64 = 50;
+
+/// the style embedded in the SVG: the hover behaviors, which must work
+/// in a standalone SVG. Colors are given as attributes so that external
+/// CSS rules (e.g. `.series-0 { color: red }`) take precedence
+static SVG_STYLE: &str = "
+g.fad { pointer-events:all; }
+g.fad g.opt { opacity:.3; }
+g.fad:hover g.opt { opacity:1; }
+g.inv g.opt { display:none; }
+g.inv:hover g.opt { display:block; }
+";
+
+/// the class of the elements of a series, allowing to restyle them
+fn series_class(seq_idx: usize) -> String {
+    format!("series-{}", seq_idx)
+}
+
+/// the look of every y series: defaults depending on the chart mode,
+/// the theme and the palette, then the overrides of the user
+fn series_styles(tbl: &Tbl, options: &GraphOptions, theme: &Theme) -> Vec<SeriesStyle> {
+    let colors = series_colors(theme, options.palette, tbl.y_seqs_count());
+    tbl.y_seqs()
+        .zip(colors)
+        .enumerate()
+        .map(|(seq_idx, (seq, color))| {
+            let pattern = SeriesPattern::new(seq_idx);
+            let width = match options.mode {
+                ChartMode::Stacked | ChartMode::Stacked100 => 2.0,
+                ChartMode::Lines | ChartMode::Scatter => 3.0,
+            };
+            let style = SeriesStyle {
+                color,
+                width,
+                opacity: 0.8,
+                dash_array: pattern
+                    .dash_array
+                    .filter(|_| options.patterns)
+                    .map(String::from),
+                marker: Some(pattern.marker).filter(|_| options.patterns),
+                interpolation: options.series_interpolation(&seq.header),
+            };
+            style.with_overrides(series_overrides(&options.styles, &seq.header))
+        })
+        .collect()
+}
+
 pub struct Graph {
     tbl: Tbl,
     width: usize,
     height: usize,
-    gr: IntRect,
-    projector: Projector,
-    scale: Scale,
-    hover: bool, // whether to build elements only visible on hover
+    x_min: i64,
+    x_max: i64,
+    panels: Vec<Panel>,
+    stack: Option<Stack>,
+    x_scale: Option<Scale>, // only in scatter mode, when x isn't a sequence of rows
+    options: GraphOptions,
+    hover: bool,         // whether to build elements only visible on hover
+    x_label_shrink: f64, // reduction of the font of the x labels, when they're too long
+    legend_shrink: f64,  // reduction of the font of the legend, when it's too long
+    theme: &'static Theme,
+    styles: Vec<SeriesStyle>, // one per y series
+    id_prefix: String,        // so that several SVGs can be in the same page
 }
 
 impl Graph {
-    pub fn new(tbl: Tbl) -> Self {
-        let width = 800;
-        let height = 500;
+    pub fn new(tbl: Tbl, mut options: GraphOptions) -> Self {
+        let layout = options.layout;
+        let (width, height) = (layout.width, layout.height);
         let x_seq = &tbl.x_seq();
-        let (y_min, y_max) = tbl.y_min_max();
-        let scale = Scale::new(y_min, y_max);
-        let sr = IntRect::new(x_seq.min, scale.max, x_seq.max - x_seq.min, -scale.range());
-        let (top, right, bottom, left) = (26, 50, 85, 70);
+        let x_scale = if options.mode == ChartMode::Scatter {
+            Some(Scale::with_bounds(
+                x_seq.min,
+                x_seq.max,
+                ZeroPolicy::Never,
+                options.x_min,
+                options.x_max,
+            ))
+        } else {
+            None
+        };
+        let (x_min, x_max): (i64, i64) = match &x_scale {
+            Some(x_scale) => (x_scale.min, x_scale.max),
+            None => {
+                let x_min = options.x_min.unwrap_or(x_seq.min);
+                (x_min, options.x_max.unwrap_or(x_seq.max).max(x_min + 1))
+            }
+        };
+        let stack = match options.mode {
+            ChartMode::Stacked => Some(Stack::new(&tbl, options.stack_missing, false)),
+            ChartMode::Stacked100 => Some(Stack::new(&tbl, options.stack_missing, true)),
+            ChartMode::Lines | ChartMode::Scatter => None,
+        };
+        if options.y_max.is_none() {
+            options.y_max = stack.as_ref().and_then(Stack::y_ceiling);
+        }
+        let default_margins = if options.facet {
+            Margins::new(8, 44, 85, 60)
+        } else {
+            Margins::new(26, 50, 85, 70)
+        };
+        let mut margins = layout.margins(default_margins);
+        let auto_margins = layout.margins.is_none();
+        let theme = options.theme.theme();
+        let styles = series_styles(&tbl, &options, theme);
+        let mut graph = Self {
+            tbl,
+            width,
+            height,
+            x_min,
+            x_max,
+            panels: Vec::new(),
+            stack,
+            x_scale,
+            options,
+            hover: true,
+            x_label_shrink: 1.0,
+            legend_shrink: 1.0,
+            theme,
+            styles,
+            id_prefix: String::new(),
+        };
+        // the titles and the legend are over the top margin, be it the
+        // default one or the one given by the user
+        margins.top += graph.header_height();
+        if graph.options.legend_stats && !graph.options.facet {
+            // the legend is a table with a header line and a line per series
+            margins.top += graph.text_height(LEGEND_LINE_HEIGHT, TextRole::Legend)
+                * graph.tbl.y_seqs_count() as i64;
+        }
+        if graph.options.facet {
+            graph.grow_for_facets(margins);
+        }
+        graph.panels = graph.build_panels(margins);
+        if auto_margins {
+            // the scales don't depend on the margins, so we can measure
+            // the labels of the first panels to compute the real margins
+            margins = graph.fit_texts(margins);
+        } else {
+            margins.top += graph.legend_overflow_height();
+        }
+        if graph.options.facet {
+            graph.grow_for_facets(margins);
+        }
+        graph.panels = graph.build_panels(margins);
+        graph
+    }
+    fn build_panels(&self, margins: Margins) -> Vec<Panel> {
+        if self.options.facet {
+            return self.facet_panels(margins);
+        }
+        let (tbl, options) = (&self.tbl, &self.options);
+        let Margins {
+            top,
+            right,
+            bottom,
+            left,
+        } = margins;
+        // the plot keeps at least a pixel, even with the margins of a
+        // graph too small for its texts
         let gr = IntRect::new(
             left,
             top,
-            width as i64 - (left + right),
-            height as i64 - (top + bottom),
+            (self.width as i64 - (left + right)).max(1),
+            (self.height as i64 - (top + bottom)).max(1),
         );
-        let projector = Projector::new(&sr, &gr);
-        Self {
+        let sides: Vec<Side> = if self.stack.is_some() {
+            // stacked series can only share the same axis
+            vec![Side::Right; tbl.y_seqs_count()]
+        } else if options.auto_axes {
+            split_by_magnitude(tbl)
+        } else {
+            tbl.y_seqs()
+                .map(|seq| {
+                    if options.left_axis.contains(&seq.header) {
+                        Side::Left
+                    } else {
+                        Side::Right
+                    }
+                })
+                .collect()
+        };
+        let series_sides: Vec<(usize, Side)> = sides.into_iter().enumerate().collect();
+        let shared_y = self.stack.as_ref().map(Stack::y_min_max);
+        vec![Panel::new(
             tbl,
-            width,
-            height,
+            options,
             gr,
-            projector,
-            scale,
-            hover: true,
+            (self.x_min, self.x_max),
+            &series_sides,
+            shared_y,
+        )]
+    }
+    /// the gaps between the panels of the facets, horizontally and
+    /// vertically: room for the y ticks, which need as much space as
+    /// at the right of the last column, and for the titles
+    fn facet_gaps(&self, margins: Margins) -> (i64, i64) {
+        (margins.right, self.scaled(22))
+    }
+    /// the number of columns and rows of the facets: a square grid,
+    /// unless the panels would be too narrow
+    fn facet_grid(&self, margins: Margins) -> (usize, usize) {
+        let count = self.tbl.y_seqs_count();
+        let (h_gap, _) = self.facet_gaps(margins);
+        let available = self.width as i64 - (margins.left + margins.right);
+        let max_cols = ((available + h_gap) / (self.scaled(FACET_MIN_WIDTH) + h_gap)).max(1);
+        let cols = ((count as f64).sqrt().ceil() as usize).min(max_cols as usize);
+        (cols, count.div_ceil(cols))
+    }
+    /// the height the graph needs so that all facets have the
+    /// minimal height
+    fn facet_min_height(&self, margins: Margins) -> i64 {
+        let (_, rows) = self.facet_grid(margins);
+        let (_, v_gap) = self.facet_gaps(margins);
+        let cell_height = self.scaled(FACET_TITLE_HEIGHT) + self.scaled(FACET_MIN_HEIGHT);
+        margins.top + margins.bottom + rows as i64 * cell_height + (rows as i64 - 1) * v_gap
+    }
+    /// make the graph taller when it can't hold all facets
+    fn grow_for_facets(&mut self, margins: Margins) {
+        let min_height = self.facet_min_height(margins);
+        if min_height > self.height as i64 {
+            info!(
+                "graph height increased from {} to {} to hold {} facets",
+                self.height,
+                min_height,
+                self.tbl.y_seqs_count(),
+            );
+            self.height = min_height as usize;
+        }
+    }
+    /// build one small panel per y series, arranged in a grid
+    fn facet_panels(&self, margins: Margins) -> Vec<Panel> {
+        let (tbl, options) = (&self.tbl, &self.options);
+        let (width, height) = (self.width, self.height);
+        let (cols, rows) = self.facet_grid(margins);
+        let Margins {
+            top,
+            right,
+            bottom,
+            left,
+        } = margins;
+        let (h_gap, v_gap) = self.facet_gaps(margins);
+        let title_height = self.scaled(FACET_TITLE_HEIGHT);
+        let cell_width = (width as i64 - (left + right) - h_gap * (cols as i64 - 1)) / cols as i64;
+        let cell_height =
+            (height as i64 - (top + bottom) - v_gap * (rows as i64 - 1)) / rows as i64;
+        let shared_y = if options.shared_y {
+            Some(tbl.y_min_max())
+        } else {
+            None
+        };
+        tbl.y_seqs()
+            .enumerate()
+            .map(|(seq_idx, seq)| {
+                let (col, row) = (seq_idx % cols, seq_idx / cols);
+                let gr = IntRect::new(
+                    left + col as i64 * (cell_width + h_gap),
+                    top + row as i64 * (cell_height + v_gap) + title_height,
+                    cell_width,
+                    cell_height - title_height,
+                );
+                let mut panel = Panel::new(
+                    tbl,
+                    options,
+                    gr,
+                    (self.x_min, self.x_max),
+                    &[(seq_idx, Side::Right)],
+                    shared_y,
+                );
+                panel.title = Some(seq.header.clone());
+                // as the x axis is shared, only the last row gets x labels
+                panel.x_labels = row + 1 == rows;
+                panel
+            })
+            .collect()
+    }
+    /// compute the margins needed by the tick labels, and the font
+    /// reductions of the x labels and of the legend when there's not
+    /// enough room even with big margins.
+    /// Texts are measured with the metrics of Helvetica, whatever the
+    /// fonts set by the user, so a much wider font may overflow
+    fn fit_texts(&mut self, mut margins: Margins) -> Margins {
+        let tick_font_size = self.font_size(TextRole::Tick);
+        let padding = self.scaled(6);
+        // y tick labels
+        let right_ticks_width = self.ticks_width(Side::Right);
+        let left_ticks_width = self.ticks_width(Side::Left);
+        // x tick labels
+        let x_labels: Vec<String> = match &self.x_scale {
+            Some(x_scale) => x_scale
+                .ticks
+                .iter()
+                .map(|tick| self.x_value_label(*tick))
+                .collect(),
+            None => {
+                let x_seq = self.tbl.x_seq();
+                (0..x_seq.len())
+                    .filter(|&idx| {
+                        x_seq.ival[idx].is_some_and(|x| x >= self.x_min && x <= self.x_max)
+                    })
+                    .filter_map(|idx| self.x_label(idx))
+                    .collect()
+            }
+        };
+        let x_labels_width = x_labels
+            .iter()
+            .map(|label| text_width(label, tick_font_size))
+            .fold(0.0, f64::max);
+        let rotated = self.x_scale.is_none() || matches!(self.tbl.x_seq().nature, Nature::Date(_));
+        let (mut bottom, mut left, right) = if rotated {
+            let extent = (x_labels_width + tick_font_size) * std::f64::consts::FRAC_1_SQRT_2;
+            let extent = extent.ceil() as i64;
+            (self.scaled(9) + extent + padding, extent + padding, 0)
+        } else {
+            let half_width = (x_labels_width / 2.0).ceil() as i64;
+            (
+                self.scaled(12) + tick_font_size.ceil() as i64 + padding,
+                half_width + padding,
+                half_width + padding,
+            )
+        };
+        // when labels are really too long, they get smaller
+        let (max_bottom, max_left) = (self.height as i64 / 3, self.width as i64 / 4);
+        if bottom > max_bottom || left > max_left {
+            let ratio = (max_bottom as f64 / bottom as f64).min(max_left as f64 / left as f64);
+            self.x_label_shrink = ratio.max(0.6);
+            bottom = bottom.min(max_bottom);
+            left = left.min(max_left);
+        }
+        if let Some(left_ticks_width) = left_ticks_width {
+            left = left.max(left_ticks_width);
+        }
+        margins.bottom = bottom.max(padding);
+        margins.left = left.max(padding);
+        margins.right = right.max(right_ticks_width.unwrap_or(0)).max(padding);
+        // direct labels, right of the curves and of their tick labels
+        if self.options.direct_labels {
+            let legend_font_size = self.font_size(TextRole::Legend);
+            let labels_width = self
+                .tbl
+                .y_seqs()
+                .map(|seq| text_width(&seq.header, legend_font_size).ceil() as i64)
+                .max()
+                .unwrap_or(0);
+            margins.right = margins
+                .right
+                .max(self.direct_labels_offset() + labels_width + padding);
+        }
+        // axis captions
+        let caption_height = self.text_height(CAPTION_HEIGHT, TextRole::Caption);
+        if self.x_caption().is_some() {
+            margins.bottom += caption_height;
+        }
+        if self.options.y_label.is_some() {
+            match self.y_caption_side() {
+                Side::Left => margins.left += caption_height,
+                Side::Right => margins.right += caption_height,
+            }
+        }
+        // legend
+        if !self.options.facet {
+            let legend_font_size = self.font_size(TextRole::Legend);
+            let labels_width = (0..self.tbl.y_seqs_count())
+                .map(|seq_idx| text_width(&self.legend_label(seq_idx), legend_font_size))
+                .fold(0.0, f64::max);
+            let available = if self.options.legend_stats {
+                self.legend_stats_first_column_x() - self.legend_label_x() - padding
+            } else {
+                self.width as i64 - self.legend_label_x() - padding
+            };
+            if labels_width > available as f64 {
+                self.legend_shrink = (available as f64 / labels_width).max(0.6);
+            }
+            margins.top += self.legend_overflow_height();
+        }
+        margins
+    }
+    /// the width taken by the labels of the y ticks on a side, with
+    /// their padding, or `None` when there's no axis on this side
+    fn ticks_width(&self, side: Side) -> Option<i64> {
+        let tick_font_size = self.font_size(TextRole::Tick);
+        let tick_format = self.tick_format();
+        self.panels
+            .iter()
+            .flat_map(|panel| panel.axes.iter())
+            .filter(|axis| axis.side == side)
+            .flat_map(|axis| axis.scale.ticks.iter())
+            .map(|tick| text_width(&tick_format.format(*tick), tick_font_size).ceil() as i64)
+            .max()
+            .map(|w| w + self.scaled(2) + self.scaled(6))
+    }
+    /// the distance from the right of the plot at which direct labels
+    /// are written when they'd overlap the tick labels
+    fn direct_labels_offset(&self) -> i64 {
+        self.ticks_width(Side::Right).unwrap_or(self.scaled(7))
+    }
+    /// the height of the rows of the legend after the first one, the
+    /// top margin only having room for one row
+    fn legend_overflow_height(&self) -> i64 {
+        if self.options.facet || self.options.legend_stats {
+            return 0;
+        }
+        let rows = self
+            .legend_positions()
+            .last()
+            .map_or(1, |&(_, row)| row + 1);
+        self.text_height(LEGEND_LINE_HEIGHT, TextRole::Legend) * (rows as i64 - 1)
+    }
+    /// the text to display for the x value at the given index: dates
+    /// are written as in the table, unless another timezone is asked
+    fn x_label(&self, idx: usize) -> Option<String> {
+        let x_seq = self.tbl.x_seq();
+        match x_seq.nature {
+            Nature::Integer => x_seq.ival[idx].map(|x| self.options.x_format.format(x)),
+            Nature::Date(_) if self.options.tz.is_some() => {
+                x_seq.ival[idx].map(|x| self.x_value_label(x))
+            }
+            _ => x_seq.raw[idx].clone(),
+        }
+    }
+    /// the text to display for a x value which may not be in the table
+    fn x_value_label(&self, x: i64) -> String {
+        match self.tbl.x_seq().nature {
+            Nature::Date(offset) => self
+                .options
+                .tz
+                .unwrap_or(Timezone::Fixed(offset))
+                .format(x)
+                .unwrap_or_else(|| x.to_string()),
+            Nature::Integer => self.options.x_format.format(x),
+        }
+    }
+    /// the format of the y ticks
+    fn tick_format(&self) -> NumberFormat {
+        match &self.stack {
+            Some(stack) if stack.normalized => NumberFormat::Percent,
+            _ => self.options.y_format,
+        }
+    }
+    /// the size of the texts of a role, scaled to the graph
+    fn font_size(&self, role: TextRole) -> f64 {
+        self.shrunk_font_size(role, 1.0)
+    }
+    fn shrunk_font_size(&self, role: TextRole, shrink: f64) -> f64 {
+        let size = role_font_size(&self.options.fonts, role);
+        self.options.layout.font_size(size * shrink)
+    }
+    /// the font family of the whole graph
+    fn font_family(&self) -> String {
+        let family = self
+            .options
+            .fonts
+            .iter()
+            .rev()
+            .filter(|setting| setting.role.is_none())
+            .find_map(|setting| setting.family.as_deref())
+            .unwrap_or(DEFAULT_FONT_FAMILY);
+        match &self.options.embedded_font {
+            Some(font) if !first_family(family).eq_ignore_ascii_case(&font.family) => {
+                format!("'{}', {}", font.family, family)
+            }
+            _ => family.to_string(),
+        }
+    }
+    /// a text with the font of its role
+    fn text<T: Into<String>>(&self, role: TextRole, content: T) -> element::Text {
+        let text = element::Text::new(content).set("font-size", self.font_size(role));
+        match role_font_family(&self.options.fonts, role) {
+            Some(family) if family != self.font_family() => text.set("font-family", family),
+            _ => text,
+        }
+    }
+    /// scale a distance related to text
+    fn scaled(&self, distance: i64) -> i64 {
+        self.options.layout.scaled(distance)
+    }
+    /// scale a height of the texts of a role, which grows when their
+    /// font is bigger than the default one
+    fn text_height(&self, height: i64, role: TextRole) -> i64 {
+        let ratio = role_font_size(&self.options.fonts, role) / role.default_size();
+        self.scaled((height as f64 * ratio).round() as i64)
+    }
+    /// the height of the title and subtitle, above the legend
+    fn header_height(&self) -> i64 {
+        let mut height = 0;
+        if self.options.title.is_some() {
+            height += self.text_height(TITLE_HEIGHT, TextRole::Title);
+        }
+        if self.options.subtitle.is_some() {
+            height += self.text_height(SUBTITLE_HEIGHT, TextRole::Subtitle);
+        }
+        height
+    }
+    /// the caption of the x axis, if any
+    fn x_caption(&self) -> Option<String> {
+        let caption = match &self.options.x_label {
+            Some(x_label) => x_label.trim(),
+            None => self.tbl.x_seq().header.trim(),
+        };
+        if caption.is_empty() {
+            None
+        } else {
+            Some(caption.to_string())
         }
     }
+    /// the side of the main y axis, where the y caption is written
+    fn y_caption_side(&self) -> Side {
+        self.panels
+            .first()
+            .and_then(|panel| panel.axes.first())
+            .map_or(Side::Right, |axis| axis.side)
+    }
+    /// the title, subtitle, and axis captions
+    fn captions_group(&self) -> node::element::Group {
+        let mut group = node::element::Group::new().set("class", "captions");
+        let mut y = 0;
+        if let Some(title) = &self.options.title {
+            y += self.text_height(TITLE_HEIGHT, TextRole::Title);
+            let text = self
+                .text(TextRole::Title, title.as_str())
+                .set("class", "title")
+                .set("x", self.width / 2)
+                .set("y", y - self.scaled(5))
+                .set("fill", self.theme.text)
+                .set("text-anchor", "middle")
+                .set("font-weight", "bold");
+            group.append(text);
+        }
+        if let Some(subtitle) = &self.options.subtitle {
+            y += self.text_height(SUBTITLE_HEIGHT, TextRole::Subtitle);
+            let text = self
+                .text(TextRole::Subtitle, subtitle.as_str())
+                .set("class", "subtitle")
+                .set("x", self.width / 2)
+                .set("y", y - self.scaled(3))
+                .set("fill", self.theme.text)
+                .set("opacity", 0.7)
+                .set("text-anchor", "middle");
+            group.append(text);
+        }
+        // the axis captions are centered on the area covered by the panels
+        let (left, top, right, bottom) = self.panels.iter().fold(
+            (i64::MAX, i64::MAX, i64::MIN, i64::MIN),
+            |(left, top, right, bottom), panel| {
+                (
+                    left.min(panel.gr.left),
+                    top.min(panel.gr.top),
+                    right.max(panel.gr.right()),
+                    bottom.max(panel.gr.bottom()),
+                )
+            },
+        );
+        if let Some(x_caption) = self.x_caption() {
+            let text = self
+                .text(TextRole::Caption, x_caption)
+                .set("class", "caption x-caption")
+                .set("x", (left + right) / 2)
+                .set("y", self.height as i64 - self.scaled(4))
+                .set("fill", self.theme.text)
+                .set("text-anchor", "middle");
+            group.append(text);
+        }
+        if let Some(y_caption) = &self.options.y_label {
+            let middle = (top + bottom) / 2;
+            let x = match self.y_caption_side() {
+                Side::Left => self.text_height(CAPTION_HEIGHT - 4, TextRole::Caption),
+                Side::Right => self.width as i64 - self.scaled(4),
+            };
+            let text = self
+                .text(TextRole::Caption, y_caption.as_str())
+                .set("class", "caption y-caption")
+                .set("x", x)
+                .set("y", middle)
+                .set("fill", self.theme.text)
+                .set("text-anchor", "middle")
+                .set("transform", format!("rotate(-90 {} {})", x, middle));
+            group.append(text);
+        }
+        group
+    }
     fn legend_group(&self) -> node::element::Group {
-        let mut group = node::element::Group::new();
-        let mut x = 0;
-        let y = 10;
-        let w = self.width / self.tbl.y_seqs_count();
-        for (seq_idx, y_seq) in self.tbl.y_seqs().enumerate() {
+        if self.options.legend_stats {
+            return self.legend_stats_group();
+        }
+        let mut group = node::element::Group::new().set("class", "legend");
+        let top = self.header_height() + self.scaled(10);
+        let line_height = self.text_height(LEGEND_LINE_HEIGHT, TextRole::Legend);
+        for (seq_idx, (x, row)) in self.legend_positions().into_iter().enumerate() {
+            let y = top + row as i64 * line_height;
+            group.append(self.legend_swatch(seq_idx, (x, y)));
+            let label = self
+                .text(TextRole::Legend, self.legend_label(seq_idx))
+                .set("x", x + self.legend_label_x())
+                .set("y", y + self.scaled(7))
+                .set("fill", self.theme.text)
+                .set(
+                    "font-size",
+                    self.shrunk_font_size(TextRole::Legend, self.legend_shrink),
+                );
+            group.append(label);
+        }
+        group
+    }
+    /// whether the legend shows pieces of curves and markers rather
+    /// than squares of color, because series don't differ only by color
+    fn legend_has_patterns(&self) -> bool {
+        self.styles
+            .iter()
+            .any(|style| style.dash_array.is_some() || style.marker.is_some())
+    }
+    /// the sample of a series in the legend, whose top left corner is
+    /// at `(x, y)`: a square of its color, or a piece of its curve
+    /// with its marker
+    fn legend_swatch(&self, seq_idx: usize, (x, y): (i64, i64)) -> node::element::Group {
+        let mut group = self.series_group(seq_idx);
+        let size = self.scaled(8);
+        let style = &self.styles[seq_idx];
+        if self.legend_has_patterns() {
+            let y = y + size / 2;
+            // the swatch shows what's drawn: curves and/or markers
+            let (has_line, has_marker) = match self.options.mode {
+                ChartMode::Lines => (true, style.marker.is_some()),
+                ChartMode::Stacked | ChartMode::Stacked100 => (true, false),
+                ChartMode::Scatter => (false, true),
+            };
+            if has_line {
+                let mut line = node::element::Line::new()
+                    .set("x1", x + self.scaled(2))
+                    .set("y1", y)
+                    .set("x2", x + self.scaled(28))
+                    .set("y2", y)
+                    .set("stroke", "currentColor")
+                    .set("stroke-width", style.width.min(4.0));
+                if let Some(dash_array) = &style.dash_array {
+                    line = line.set("stroke-dasharray", dash_array.as_str());
+                }
+                group.append(line);
+            }
+            if has_marker {
+                group.append(self.marker(seq_idx, (x + self.scaled(15), y)));
+            }
+        } else {
             let square = node::element::Rectangle::new()
-                .set("x", x + 4)
-                .set("y", 10)
-                .set("width", 8)
-                .set("height", 8)
-                .set("fill", COLORS[seq_idx]);
+                .set("x", x + self.scaled(4))
+                .set("y", y)
+                .set("width", size)
+                .set("height", size)
+                .set("fill", "currentColor");
             group.append(square);
-            let label = element::Text::new(&y_seq.header)
-                .set("x", x + 14)
-                .set("y", y + 7)
-                .set("fill", LEGEND_COLOR)
-                .set("font-size", 10);
+        }
+        group
+    }
+    /// the position of the label of a series in the legend, relative
+    /// to its swatch
+    fn legend_label_x(&self) -> i64 {
+        if self.legend_has_patterns() {
+            self.scaled(32)
+        } else {
+            self.scaled(14)
+        }
+    }
+    /// the position of the entry of each series in the legend: its x
+    /// and its row, the entries wrapping when they don't fit in a row
+    fn legend_positions(&self) -> Vec<(i64, usize)> {
+        let font_size = self.shrunk_font_size(TextRole::Legend, self.legend_shrink);
+        let (label_x, gap) = (self.legend_label_x(), self.scaled(16));
+        let mut positions = Vec::new();
+        let (mut x, mut row) = (0, 0);
+        for seq_idx in 0..self.tbl.y_seqs_count() {
+            let entry_width =
+                label_x + text_width(&self.legend_label(seq_idx), font_size).ceil() as i64;
+            if x > 0 && x + entry_width > self.width as i64 {
+                x = 0;
+                row += 1;
+            }
+            positions.push((x, row));
+            x += entry_width + gap;
+        }
+        positions
+    }
+    /// change the colors of the graph
+    pub fn set_theme(&mut self, theme: &'static Theme) {
+        self.theme = theme;
+        self.styles = series_styles(&self.tbl, &self.options, theme);
+    }
+    /// the prefix of the ids of the SVG elements
+    pub fn id_prefix(&self) -> &str {
+        &self.id_prefix
+    }
+    /// change the prefix of the ids of the SVG elements, which must be
+    /// unique among the SVGs of a page
+    pub fn set_id_prefix<S: Into<String>>(&mut self, prefix: S) {
+        self.id_prefix = prefix.into();
+    }
+    /// the id of the clip path of a panel
+    fn plot_clip_id(&self, panel_idx: usize) -> String {
+        format!("{}plot-clip-{}", self.id_prefix, panel_idx)
+    }
+    /// the id of the clip path of the part of a panel above
+    /// (or below) the threshold
+    fn threshold_clip_id(&self, panel_idx: usize, above: bool) -> String {
+        let side = if above { "above" } else { "below" };
+        format!("{}{}-clip-{}", self.id_prefix, side, panel_idx)
+    }
+    /// the color of a series
+    fn color(&self, seq_idx: usize) -> &str {
+        &self.styles[seq_idx].color
+    }
+    /// a group for elements of a series, which are drawn with its color
+    /// as "currentColor", so that a `.series-N { color: ... }` CSS rule
+    /// changes them all
+    fn series_group(&self, seq_idx: usize) -> node::element::Group {
+        node::element::Group::new()
+            .set("class", series_class(seq_idx))
+            .set("color", self.color(seq_idx))
+    }
+    /// the marker of a point of a series, a circle when the series
+    /// has no marker shape. It must be in a series group
+    fn marker(&self, seq_idx: usize, point: (i64, i64)) -> element::Path {
+        let shape = self.styles[seq_idx].marker.unwrap_or(MarkerShape::Circle);
+        let r = self.options.layout.text_scale().min(1.5) * 3.0;
+        element::Path::new()
+            .set("class", "marker")
+            .set("fill", "currentColor")
+            .set("stroke", self.theme.background)
+            .set("stroke-width", 0.5)
+            .set("d", shape.data(point, r))
+    }
+    /// the name of the series in the legend, with the side of its axis
+    /// when there are several ones
+    fn legend_label(&self, seq_idx: usize) -> String {
+        let header = &self.tbl.y_seq(seq_idx).header;
+        let panel = &self.panels[0];
+        if panel.axes.len() > 1 {
+            let side = panel.seq_axis(seq_idx).side;
+            format!("{} {}", header, side.legend_mark())
+        } else {
+            header.clone()
+        }
+    }
+    /// the position of the first column of statistics in the legend
+    fn legend_stats_first_column_x(&self) -> i64 {
+        self.width as i64 - self.scaled(70) * 5
+    }
+    /// the legend as a table, with statistics of the series
+    fn legend_stats_group(&self) -> node::element::Group {
+        let mut group = node::element::Group::new().set("class", "legend legend-stats");
+        let columns = ["min", "max", "mean", "last", "p95"];
+        let column_width = self.scaled(70);
+        let first_column_x = self.legend_stats_first_column_x();
+        let cell = |text: String, x: i64, y: i64, color: &str| {
+            self.text(TextRole::Legend, text)
+                .set("x", x)
+                .set("y", y)
+                .set("fill", color)
+                .set("text-anchor", "end")
+                .set("font-size", self.shrunk_font_size(TextRole::Legend, 0.9))
+        };
+        let mut y = self.header_height() + self.scaled(10);
+        let line_height = self.text_height(LEGEND_LINE_HEIGHT, TextRole::Legend);
+        let text_dy = self.scaled(7);
+        for (idx, column) in columns.iter().enumerate() {
+            let x = first_column_x + column_width * (idx as i64 + 1) - self.scaled(10);
+            group.append(
+                cell(column.to_string(), x, y + text_dy, self.theme.tick).set("opacity", 0.6),
+            );
+        }
+        let stats = self.stats();
+        for (seq_idx, y_seq) in self.tbl.y_seqs().enumerate() {
+            y += line_height;
+            group.append(self.legend_swatch(seq_idx, (0, y)));
+            let label = self
+                .text(TextRole::Legend, self.legend_label(seq_idx))
+                .set("x", self.legend_label_x())
+                .set("y", y + text_dy)
+                .set("fill", self.theme.text)
+                .set(
+                    "font-size",
+                    self.shrunk_font_size(TextRole::Legend, self.legend_shrink),
+                );
             group.append(label);
-            x += w;
+            let s = match stats.iter().find(|s| s.header == y_seq.header) {
+                Some(s) => s,
+                None => {
+                    continue;
+                }
+            };
+            let format = self.options.series_format(&y_seq.header);
+            let values = [s.min, s.max, s.mean.round() as i64, s.last, s.p95];
+            for (idx, value) in values.iter().enumerate() {
+                let x = first_column_x + column_width * (idx as i64 + 1) - self.scaled(10);
+                group.append(cell(format.format(*value), x, y + text_dy, self.theme.text));
+            }
         }
         group
     }
-    fn y_scale_group(&self) -> node::element::Group {
+    /// compute the statistics of the y series, on the displayed range of x
+    pub fn stats(&self) -> Vec<SeriesStats> {
+        let x_seq = self.tbl.x_seq();
+        self.tbl
+            .y_seqs()
+            .filter_map(|y_seq| {
+                let values: Vec<i64> = x_seq
+                    .ival
+                    .iter()
+                    .zip(&y_seq.ival)
+                    .filter_map(|(x, y)| match (x, y) {
+                        (Some(x), Some(y)) if *x >= self.x_min && *x <= self.x_max => Some(*y),
+                        _ => None,
+                    })
+                    .collect();
+                SeriesStats::new(&y_seq.header, &values)
+            })
+            .collect()
+    }
+    /// the title of a panel, written over its top left corner
+    fn panel_title_group(&self, panel: &Panel) -> node::element::Group {
         let mut group = node::element::Group::new();
-        let x_seq = &self.tbl.x_seq();
-        for tick in &self.scale.ticks {
+        if let Some(title) = &panel.title {
+            let mut label = self
+                .text(TextRole::Legend, title)
+                .set("class", "panel-title")
+                .set("x", panel.gr.left)
+                .set("y", panel.gr.top - self.scaled(4))
+                .set("fill", self.theme.text);
+            if let Some((seq_idx, _)) = panel.series.first() {
+                label = label
+                    .set("class", format!("panel-title {}", series_class(*seq_idx)))
+                    .set("color", self.color(*seq_idx))
+                    .set("fill", "currentColor");
+            }
+            group.append(label);
+        }
+        group
+    }
+    /// the background bands of a time axis, drawn beneath everything else
+    fn stripes_group(&self, panel: &Panel) -> node::element::Group {
+        let mut group = node::element::Group::new().set("class", "stripes");
+        let tz = match self.tbl.x_seq().nature {
+            Nature::Date(offset) => self.options.tz.unwrap_or(Timezone::Fixed(offset)),
+            Nature::Integer => {
+                return group;
+            }
+        };
+        let (min, max) = (self.x_min, self.x_max);
+        let mut layers = Vec::new();
+        if let Some(period) = self.options.stripes {
+            layers.push((
+                period_bands(min, max, tz, period),
+                "stripe",
+                self.theme.stripe,
+            ));
+        }
+        if self.options.weekends {
+            layers.push((weekend_bands(min, max, tz), "weekend", self.theme.weekend));
+        }
+        if let Some(hours) = self.options.night_hours {
+            layers.push((hour_bands(min, max, tz, hours), "night", self.theme.night));
+        }
+        let gr = &panel.gr;
+        for (bands, class, shade) in layers {
+            for (start, end) in bands {
+                let left = panel.project_x(start);
+                let right = panel.project_x(end);
+                let rect = element::Rectangle::new()
+                    .set("class", class)
+                    .set("x", left)
+                    .set("y", gr.top)
+                    .set("width", right - left)
+                    .set("height", gr.height)
+                    .set("fill", shade.color)
+                    .set("fill-opacity", shade.opacity);
+                group.append(rect);
+            }
+        }
+        group
+    }
+    fn y_scale_group(&self, panel: &Panel) -> node::element::Group {
+        let mut group = node::element::Group::new().set("class", "axis y-axis");
+        let gr = &panel.gr;
+        for (axis_idx, axis) in panel.axes.iter().enumerate() {
+            let (label_x, anchor) = match axis.side {
+                Side::Left => (gr.left - 2, "end"),
+                Side::Right => (gr.right() + 2, "left"),
+            };
+            for tick in &axis.scale.ticks {
+                let y = axis.projector.project_y(*tick);
+                if axis_idx == 0 {
+                    // only the main axis gets lines across the plot
+                    let data = element::path::Data::new()
+                        .move_to((gr.left, y))
+                        .horizontal_line_to(gr.right());
+                    let path = element::Path::new()
+                        .set("class", "grid")
+                        .set("fill", "none")
+                        .set("stroke", self.theme.grid)
+                        .set("stroke-width", 1)
+                        .set("opacity", 0.4)
+                        .set("stroke-dasharray", "10 7")
+                        .set("d", data);
+                    group.append(path);
+                } else {
+                    let x = match axis.side {
+                        Side::Left => gr.left,
+                        Side::Right => gr.right() - 4,
+                    };
+                    let data = element::path::Data::new()
+                        .move_to((x, y))
+                        .horizontal_line_to(x + 4);
+                    let path = element::Path::new()
+                        .set("class", "tick")
+                        .set("fill", "none")
+                        .set("stroke", self.theme.grid)
+                        .set("stroke-width", 1)
+                        .set("opacity", 0.6)
+                        .set("d", data);
+                    group.append(path);
+                }
+                let tick_label = self
+                    .text(TextRole::Tick, self.tick_format().format(*tick))
+                    .set("class", "tick")
+                    .set("x", label_x)
+                    .set("y", y + self.scaled(2))
+                    .set("fill", self.theme.tick)
+                    .set("text-anchor", anchor);
+                group.append(tick_label);
+            }
+        }
+        group
+    }
+    /// the x ticks when x is a scale, with one tick per round value
+    fn x_scale_group(&self, panel: &Panel, x_scale: &Scale) -> node::element::Group {
+        let mut group = node::element::Group::new().set("class", "axis x-axis");
+        let y = panel.gr.bottom();
+        let rotate = matches!(self.tbl.x_seq().nature, Nature::Date(_));
+        for tick in &x_scale.ticks {
+            let x = panel.project_x(*tick);
             let data = element::path::Data::new()
-                .move_to(self.projector.project_point((x_seq.min, *tick)))
-                .horizontal_line_to(self.projector.project_x(x_seq.max));
+                .move_to((x, panel.gr.top))
+                .vertical_line_to(y + 3);
             let path = element::Path::new()
+                .set("class", "grid")
                 .set("fill", "none")
-                .set("stroke", TICK_LINE_COLOR)
+                .set("stroke", self.theme.grid)
                 .set("stroke-width", 1)
-                .set("opacity", 0.4)
-                .set("stroke-dasharray", "10 7")
+                .set("opacity", 0.5)
+                .set("stroke-dasharray", "1 3")
                 .set("d", data);
             group.append(path);
-            let tick_label = element::Text::new(tick.to_string())
-                .set("x", self.gr.left + self.gr.width + 2)
-                .set("y", self.projector.project_y(*tick) + 2)
-                .set("fill", TICK_LABEL_COLOR)
-                .set("text-anchor", "left")
-                .set("font-size", 8);
+            let mut tick_label = self
+                .text(TextRole::Tick, self.x_value_label(*tick))
+                .set("class", "tick")
+                .set("y", y + self.scaled(12))
+                .set("fill", self.theme.tick)
+                .set(
+                    "font-size",
+                    self.shrunk_font_size(TextRole::Tick, self.x_label_shrink),
+                );
+            if rotate {
+                tick_label = tick_label.set("x", x + 1).set("text-anchor", "end").set(
+                    "transform",
+                    format!("rotate(-45 {} {})", x + 1, y + self.scaled(9)),
+                );
+            } else {
+                tick_label = tick_label.set("x", x).set("text-anchor", "middle");
+            }
             group.append(tick_label);
         }
         group
     }
-    fn x_ticks_group(&self) -> node::element::Group {
-        let mut group = node::element::Group::new();
+    fn x_ticks_group(&self, panel: &Panel) -> node::element::Group {
+        if let Some(x_scale) = &self.x_scale {
+            return self.x_scale_group(panel, x_scale);
+        }
+        let mut group = node::element::Group::new().set("class", "axis x-axis");
         let x_seq = &self.tbl.x_seq();
-        let y = self.gr.bottom();
+        let y = panel.gr.bottom();
         struct Tick {
             idx: usize,
             x: i64,
@@ -104,7 +1011,11 @@ impl Graph {
         }
         let mut ticks = Vec::new();
         for idx in 0..x_seq.len() {
-            let x = self.projector.project_x(x_seq.ival[idx].unwrap());
+            let x = x_seq.ival[idx].unwrap();
+            if x < self.x_min || x > self.x_max {
+                continue;
+            }
+            let x = panel.project_x(x);
             ticks.push(Tick {
                 idx,
                 x,
@@ -114,7 +1025,14 @@ impl Graph {
         }
         // we improve the ticks position to avoid overlap
         let dots = ticks.iter().map(|t| t.x).collect();
-        if let Some(dots) = unoverlap(dots, 10) {
+        if ticks.len() < 3 {
+            // no tick to move or hide
+        } else if ticks[ticks.len() - 1].x <= ticks[0].x {
+            // all ticks at the same place, only the first one is shown
+            for tick in &mut ticks[1..] {
+                tick.vis = Visibility::Invisible;
+            }
+        } else if let Some(dots) = unoverlap(dots, self.scaled(10)) {
             // we can show all ticks
             for (idx, dot) in dots.iter().enumerate() {
                 if idx != 0 && idx != ticks.len() - 1 {
@@ -130,50 +1048,61 @@ impl Graph {
         }
         // and we draw them
         for tick in ticks {
-            let mut tick_group = node::element::Group::new().set("class", tick.vis.css_class());
+            let mut tick_group = node::element::Group::new()
+                .set("class", tick.vis.css_class())
+                .set("data-x", x_seq.ival[tick.idx].unwrap());
             let data = element::path::Data::new()
-                .move_to((tick.x, self.gr.top))
+                .move_to((tick.x, panel.gr.top))
                 .vertical_line_to(y);
             let hoverable_path = element::Path::new()
                 .set("fill", "none")
-                .set("stroke", TICK_LINE_COLOR)
+                .set("stroke", self.theme.grid)
                 .set("stroke-width", 4)
                 .set("opacity", 0)
                 .set("d", data.clone());
             tick_group.append(hoverable_path);
             let path = element::Path::new()
+                .set("class", "grid")
                 .set("fill", "none")
-                .set("stroke", TICK_LINE_COLOR)
+                .set("stroke", self.theme.grid)
                 .set("stroke-width", 1)
                 .set("stroke-dasharray", "1 3")
                 .set("opacity", 0.5)
                 .set("d", data);
             tick_group.append(path);
+            if !panel.x_labels {
+                group.append(tick_group);
+                continue;
+            }
             // the opt_group may be hidden or faded when not hovered, depending
             // on tick.vis
             let mut tick_opt_group = node::element::Group::new().set("class", "opt");
             let data = element::path::Data::new()
                 .move_to((tick.x, y - 3))
                 .vertical_line_to(y)
-                .line_to((tick.tx, y + 7));
+                .line_to((tick.tx, y + self.scaled(7)));
             let path = element::Path::new()
+                .set("class", "tick")
                 .set("fill", "none")
-                .set("stroke", TICK_LINE_COLOR)
+                .set("stroke", self.theme.grid)
                 .set("stroke-width", 1)
                 .set("opacity", 0.5)
                 .set("d", data);
             tick_opt_group.append(path);
-            let tick_label = element::Text::new(
-                    x_seq.raw[tick.idx].as_ref().unwrap()
-                )
+            let tick_label = self
+                .text(TextRole::Tick, self.x_label(tick.idx).unwrap())
+                .set("class", "tick")
                 .set("x", tick.tx + 1)
-                .set("y", y + 9)
-                .set("fill", TICK_LABEL_COLOR)
+                .set("y", y + self.scaled(9))
+                .set("fill", self.theme.tick)
                 .set("text-anchor", "end")
-                .set("font-size", 8)
+                .set(
+                    "font-size",
+                    self.shrunk_font_size(TextRole::Tick, self.x_label_shrink),
+                )
                 .set(
                     "transform",
-                    format!("rotate(-45 {} {})", tick.tx + 1, y + 9),
+                    format!("rotate(-45 {} {})", tick.tx + 1, y + self.scaled(9)),
                 );
             tick_opt_group.append(tick_label);
             tick_group.append(tick_opt_group);
@@ -181,92 +1110,736 @@ impl Graph {
         }
         group
     }
-    fn curbs_group(&self) -> node::element::Group {
+    /// the clip path preventing curves from being drawn outside
+    /// the plot rect when the axis bounds are forced
+    fn plot_clip_path(&self, panel_idx: usize, panel: &Panel) -> node::element::ClipPath {
+        let rect = node::element::Rectangle::new()
+            .set("x", panel.gr.left)
+            .set("y", panel.gr.top)
+            .set("width", panel.gr.width)
+            .set("height", panel.gr.height);
+        node::element::ClipPath::new()
+            .set("id", self.plot_clip_id(panel_idx))
+            .add(rect)
+    }
+    /// a point only visible on hover, with its label (which may
+    /// be made of several lines). Callers set its `data-x`, which
+    /// lets a dashboard show the points of the same x in other graphs
+    fn hover_point(
+        &self,
+        seq_idx: usize,
+        (x, y): (i64, i64),
+        lines: &[String],
+    ) -> node::element::Group {
+        let mut point_group = node::element::Group::new()
+            .set("class", format!("inv {}", series_class(seq_idx)))
+            .set("color", self.color(seq_idx));
+        let circle = node::element::Circle::new()
+            .set("fill", "currentColor")
+            .set("cx", x)
+            .set("cy", y)
+            .set("opacity", 0)
+            .set("r", 8);
+        point_group.append(circle);
+        let mut point_opt_group = node::element::Group::new().set("class", "opt");
+        let line_y = |line_idx: usize| {
+            y - self.scaled(10) - self.scaled(10) * (lines.len() - 1 - line_idx) as i64
+        };
+        for (line_idx, line) in lines.iter().enumerate() {
+            let point_label_shadow = self
+                .text(TextRole::Label, line)
+                .set("class", "shadow")
+                .set("x", x - 5)
+                .set("y", line_y(line_idx))
+                .set("stroke", self.theme.background)
+                .set("stroke-width", 5)
+                .set("text-anchor", "end");
+            point_opt_group.append(point_label_shadow);
+        }
+        let circle = node::element::Circle::new()
+            .set("fill", "currentColor")
+            .set("cx", x)
+            .set("cy", y)
+            .set("r", 4);
+        point_opt_group.append(circle);
+        for (line_idx, line) in lines.iter().enumerate() {
+            let point_label = self
+                .text(TextRole::Label, line)
+                .set("class", "label")
+                .set("x", x - 5)
+                .set("y", line_y(line_idx))
+                .set("fill", self.theme.tick)
+                .set("text-anchor", "end");
+            point_opt_group.append(point_label);
+        }
+        point_group.append(point_opt_group);
+        point_group
+    }
+    /// the horizontal reference lines, with their labels at the axis
+    fn hlines_group(&self, panel: &Panel) -> node::element::Group {
+        let mut group = node::element::Group::new().set("class", "hlines");
+        let gr = &panel.gr;
+        let axis = &panel.axes[0];
+        for hline in &self.options.hlines {
+            if !axis.scale.contains(hline.value) {
+                continue;
+            }
+            let color = hline.color.as_deref().unwrap_or(self.theme.hline);
+            let y = axis.projector.project_y(hline.value);
+            let data = element::path::Data::new()
+                .move_to((gr.left, y))
+                .horizontal_line_to(gr.right());
+            let path = element::Path::new()
+                .set("class", "hline")
+                .set("fill", "none")
+                .set("stroke", color)
+                .set("stroke-width", 1.5)
+                .set("stroke-dasharray", "6 3")
+                .set("d", data);
+            group.append(path);
+            if let Some(label) = &hline.label {
+                let (x, anchor) = match axis.side {
+                    Side::Left => (gr.left + 3, "start"),
+                    Side::Right => (gr.right() - 3, "end"),
+                };
+                let label_shadow = self
+                    .text(TextRole::Label, label.as_str())
+                    .set("class", "shadow")
+                    .set("x", x)
+                    .set("y", y - self.scaled(3))
+                    .set("stroke", self.theme.background)
+                    .set("stroke-width", 4)
+                    .set("text-anchor", anchor);
+                group.append(label_shadow);
+                let label = self
+                    .text(TextRole::Label, label.as_str())
+                    .set("class", "hline")
+                    .set("x", x)
+                    .set("y", y - self.scaled(3))
+                    .set("fill", color)
+                    .set("text-anchor", anchor);
+                group.append(label);
+            }
+        }
+        group
+    }
+    /// the clip paths of the parts of the plot above and below the
+    /// first reference line, when curves must be colored above it
+    fn threshold_clip_paths(
+        &self,
+        panel_idx: usize,
+        panel: &Panel,
+    ) -> Option<[node::element::ClipPath; 2]> {
+        self.options.above_color.as_ref()?;
+        let threshold = self.options.hlines.first()?.value;
+        let gr = &panel.gr;
+        let y = panel.axes[0]
+            .projector
+            .project_y(threshold)
+            .clamp(gr.top, gr.bottom());
+        let clip_path = |above: bool, top: i64, height: i64| {
+            let rect = node::element::Rectangle::new()
+                .set("x", gr.left)
+                .set("y", top)
+                .set("width", gr.width)
+                .set("height", height);
+            node::element::ClipPath::new()
+                .set("id", self.threshold_clip_id(panel_idx, above))
+                .add(rect)
+        };
+        Some([
+            clip_path(true, gr.top, y - gr.top),
+            clip_path(false, y, gr.bottom() - y),
+        ])
+    }
+    /// the events, as vertical markers or shaded ranges, with their labels
+    /// along the top of the plot
+    fn events_group(&self, panel: &Panel) -> node::element::Group {
+        let mut group = node::element::Group::new().set("class", "events");
+        let gr = &panel.gr;
+        let events: Vec<&Event> = self
+            .options
+            .events
+            .iter()
+            .filter(|e| e.overlaps(self.x_min, self.x_max))
+            .collect();
+        // the labels are placed at the start of the events, moved
+        // to avoid overlapping, or only shown on hover when impossible
+        let starts: Vec<i64> = events
+            .iter()
+            .map(|event| panel.project_x(event.start.max(self.x_min)))
+            .collect();
+        let (label_xs, label_vis) = match spread(
+            &starts,
+            self.scaled(10),
+            gr.left,
+            gr.right() - self.scaled(10),
+        ) {
+            Some(label_xs) => (label_xs, Visibility::Visible),
+            None => (starts, Visibility::Invisible),
+        };
+        for (event, label_x) in events.iter().zip(label_xs) {
+            let color = event.color.as_deref().unwrap_or(self.theme.event);
+            let mut event_group = node::element::Group::new().set("class", label_vis.css_class());
+            let x = panel.project_x(event.start.max(self.x_min));
+            match event.end {
+                Some(end) => {
+                    let right = panel.project_x(end.min(self.x_max));
+                    let rect = element::Rectangle::new()
+                        .set("x", x)
+                        .set("y", gr.top)
+                        .set("width", (right - x).max(1))
+                        .set("height", gr.height)
+                        .set("fill", color)
+                        .set("fill-opacity", 0.15);
+                    event_group.append(rect);
+                }
+                None => {
+                    let data = element::path::Data::new()
+                        .move_to((x, gr.top))
+                        .vertical_line_to(gr.bottom());
+                    let hoverable_path = element::Path::new()
+                        .set("fill", "none")
+                        .set("stroke", color)
+                        .set("stroke-width", 6)
+                        .set("opacity", 0)
+                        .set("d", data.clone());
+                    event_group.append(hoverable_path);
+                    let path = element::Path::new()
+                        .set("fill", "none")
+                        .set("stroke", color)
+                        .set("stroke-width", 1)
+                        .set("stroke-dasharray", "4 2")
+                        .set("d", data);
+                    event_group.append(path);
+                }
+            }
+            let mut event_opt_group = node::element::Group::new().set("class", "opt");
+            let (label_y, transform) = (
+                gr.top + 3,
+                format!("rotate(-90 {} {})", label_x + self.scaled(10), gr.top + 3),
+            );
+            let label_shadow = self
+                .text(TextRole::Label, event.label.as_str())
+                .set("class", "shadow")
+                .set("x", label_x + self.scaled(10))
+                .set("y", label_y)
+                .set("stroke", self.theme.background)
+                .set("stroke-width", 4)
+                .set("text-anchor", "end")
+                .set("transform", transform.clone());
+            event_opt_group.append(label_shadow);
+            let label = self
+                .text(TextRole::Label, event.label.as_str())
+                .set("x", label_x + self.scaled(10))
+                .set("y", label_y)
+                .set("fill", color)
+                .set("text-anchor", "end")
+                .set("transform", transform);
+            event_opt_group.append(label);
+            event_group.append(event_opt_group);
+            group.append(event_group);
+        }
+        group
+    }
+    /// the series drawn as areas stacked on each other
+    fn stack_group(&self, panel_idx: usize, panel: &Panel, stack: &Stack) -> node::element::Group {
         let mut group = node::element::Group::new();
+        let mut areas_group = node::element::Group::new().set("class", "data").set(
+            "clip-path",
+            format!("url(#{})", self.plot_clip_id(panel_idx)),
+        );
+        let mut points_groups = Vec::new();
+        let axis = &panel.axes[0];
         let x_seq = &self.tbl.x_seq();
+        let xs: Vec<i64> = x_seq
+            .ival
+            .iter()
+            .map(|x| panel.project_x(x.unwrap()))
+            .collect();
         for (seq_idx, y_seq) in self.tbl.y_seqs().enumerate() {
+            let y_format = self.options.series_format(&y_seq.header);
+            let tops: Vec<i64> = stack.tops[seq_idx]
+                .iter()
+                .map(|&y| axis.projector.project_yf(y))
+                .collect();
+            let bottoms: Vec<i64> = match stack.bottoms(seq_idx) {
+                Some(bottoms) => bottoms
+                    .iter()
+                    .map(|&y| axis.projector.project_yf(y))
+                    .collect(),
+                None => vec![axis.projector.project_y(0); xs.len()],
+            };
+            let mut top_data = element::path::Data::new().move_to((xs[0], tops[0]));
+            for idx in 1..xs.len() {
+                top_data = top_data.line_to((xs[idx], tops[idx]));
+            }
+            let mut area_data = top_data.clone();
+            for idx in (0..xs.len()).rev() {
+                area_data = area_data.line_to((xs[idx], bottoms[idx]));
+            }
+            let mut series_group = self.series_group(seq_idx);
+            let area = element::Path::new()
+                .set("class", "area")
+                .set("fill", "currentColor")
+                .set("stroke", "none")
+                .set("opacity", 0.5)
+                .set("d", area_data.close());
+            series_group.append(area);
+            let style = &self.styles[seq_idx];
+            let mut curve = element::Path::new()
+                .set("class", "curve")
+                .set("fill", "none")
+                .set("stroke", "currentColor")
+                .set("stroke-width", style.width)
+                .set("opacity", style.opacity)
+                .set("stroke-linejoin", "round")
+                .set("d", top_data);
+            if let Some(dash_array) = &style.dash_array {
+                curve = curve.set("stroke-dasharray", dash_array.as_str());
+            }
+            series_group.append(curve);
+            areas_group.append(series_group);
             let mut points_group = node::element::Group::new();
-            let mut curve_data = element::path::Data::new();
-            let mut started = false;
+            for idx in 0..xs.len() {
+                if let (Some(x_label), Some(y)) = (self.x_label(idx), y_seq.ival[idx]) {
+                    let mut label = format!("{}, {}", x_label, y_format.format(y));
+                    if stack.normalized && stack.totals[idx] != 0.0 {
+                        let share = y as f64 * 100.0 / stack.totals[idx];
+                        label = format!("{} ({:.0}%)", label, share);
+                    }
+                    let x = x_seq.ival[idx].unwrap();
+                    let visible = x >= self.x_min
+                        && x <= self.x_max
+                        && axis.scale.contains(stack.tops[seq_idx][idx].round() as i64);
+                    if self.hover && visible {
+                        let point = self.hover_point(seq_idx, (xs[idx], tops[idx]), &[label]);
+                        points_group.append(point.set("data-x", x));
+                    }
+                }
+            }
+            points_groups.push(points_group);
+        }
+        group.append(areas_group);
+        for points_group in points_groups {
+            group.append(points_group);
+        }
+        group
+    }
+    /// the series drawn as independent points, with optional trend lines
+    fn scatter_group(&self, panel_idx: usize, panel: &Panel) -> node::element::Group {
+        let mut group = node::element::Group::new();
+        let mut markers_group = node::element::Group::new().set("class", "data").set(
+            "clip-path",
+            format!("url(#{})", self.plot_clip_id(panel_idx)),
+        );
+        let x_seq = &self.tbl.x_seq();
+        let mut points_groups = Vec::new();
+        for (seq_idx, axis_idx) in &panel.series {
+            let (seq_idx, axis) = (*seq_idx, &panel.axes[*axis_idx]);
+            let y_seq = self.tbl.y_seq(seq_idx);
+            let mut points_group = node::element::Group::new();
+            let mut series_group = self.series_group(seq_idx);
+            let mut values = Vec::new();
             for idx in 0..y_seq.len() {
-                let p = (
-                    x_seq.raw.get(idx),
-                    x_seq.ival.get(idx),
-                    y_seq.raw.get(idx),
-                    y_seq.ival.get(idx),
+                let (x, y) = match (x_seq.ival[idx], y_seq.ival[idx]) {
+                    (Some(x), Some(y)) => (x, y),
+                    _ => {
+                        continue;
+                    }
+                };
+                values.push((x as f64, y as f64));
+                let (px, py) = axis.projector.project_point((x, y));
+                series_group.append(
+                    self.marker(seq_idx, (px, py))
+                        .set("opacity", self.styles[seq_idx].opacity),
                 );
-                if let (Some(Some(raw_x)), Some(Some(x)), Some(Some(raw_y)), Some(Some(y))) = p {
-                    let (x, y) = self.projector.project_point((*x, *y));
-                    let label = format!("{}, {}", raw_x, raw_y);
-                    if started {
-                        curve_data = curve_data.line_to((x, y));
-                    } else {
-                        curve_data = curve_data.move_to((x, y));
-                        started = true;
+                let visible = x >= self.x_min && x <= self.x_max && axis.scale.contains(y);
+                if self.hover && visible {
+                    // the label shows the whole row
+                    let lines: Vec<String> = self
+                        .tbl
+                        .seqs()
+                        .enumerate()
+                        .filter_map(|(col_idx, seq)| {
+                            let cell = if col_idx == 0 && matches!(seq.nature, Nature::Date(_)) {
+                                self.x_label(idx)
+                            } else {
+                                seq.raw[idx].clone()
+                            };
+                            cell.map(|cell| format!("{}: {}", seq.header, cell))
+                        })
+                        .collect();
+                    let point = self.hover_point(seq_idx, (px, py), &lines);
+                    points_group.append(point.set("data-x", x));
+                }
+            }
+            if self.options.trend {
+                if let Some(trend) = Trend::new(&values) {
+                    let (x0, x1) = (self.x_min, self.x_max);
+                    let data = element::path::Data::new()
+                        .move_to((
+                            panel.project_x(x0),
+                            axis.projector.project_yf(trend.y(x0 as f64)),
+                        ))
+                        .line_to((
+                            panel.project_x(x1),
+                            axis.projector.project_yf(trend.y(x1 as f64)),
+                        ));
+                    let line = element::Path::new()
+                        .set("class", "trend")
+                        .set("fill", "none")
+                        .set("stroke", "currentColor")
+                        .set("stroke-width", 2)
+                        .set("stroke-dasharray", "6 4")
+                        .set("opacity", 0.8)
+                        .set("d", data);
+                    series_group.append(line);
+                }
+            }
+            markers_group.append(series_group);
+            points_groups.push(points_group);
+        }
+        group.append(markers_group);
+        for points_group in points_groups {
+            group.append(points_group);
+        }
+        group
+    }
+    fn curbs_group(&self, panel_idx: usize, panel: &Panel) -> node::element::Group {
+        let mut group = node::element::Group::new();
+        let mut curves_group = node::element::Group::new().set("class", "data").set(
+            "clip-path",
+            format!("url(#{})", self.plot_clip_id(panel_idx)),
+        );
+        let x_seq = &self.tbl.x_seq();
+        let threshold = self.threshold_clip_paths(panel_idx, panel).is_some();
+        let mut points_groups = Vec::new();
+        let mut last_points = Vec::new();
+        let mut callouts_group = node::element::Group::new();
+        for (seq_idx, axis_idx) in &panel.series {
+            let (seq_idx, axis) = (*seq_idx, &panel.axes[*axis_idx]);
+            let y_seq = self.tbl.y_seq(seq_idx);
+            let y_format = self.options.series_format(&y_seq.header);
+            let style = &self.styles[seq_idx];
+            let gaps = self.options.series_gaps(&y_seq.header);
+            let mut points_group = node::element::Group::new();
+            let mut segments: Vec<Vec<(i64, i64)>> = vec![Vec::new()];
+            let mut last_x: Option<i64> = None;
+            let mut last_point: Option<(i64, i64)> = None;
+            let mut min_point: Option<(i64, (i64, i64))> = None;
+            let mut max_point: Option<(i64, (i64, i64))> = None;
+            for idx in 0..y_seq.len() {
+                let x = match x_seq.ival[idx] {
+                    Some(x) => x,
+                    None => {
+                        continue;
+                    }
+                };
+                let y = match (y_seq.ival[idx], gaps) {
+                    (Some(y), _) => y,
+                    (None, GapPolicy::Connect) => {
+                        continue;
+                    }
+                    (None, GapPolicy::Break) => {
+                        segments.push(Vec::new());
+                        continue;
+                    }
+                    (None, GapPolicy::Zero) => 0,
+                };
+                if let (Some(max_gap), Some(last_x)) = (self.options.max_gap, last_x) {
+                    if x - last_x > max_gap {
+                        segments.push(Vec::new());
+                    }
+                }
+                last_x = Some(x);
+                let (px, py) = axis.projector.project_point((x, y));
+                segments.last_mut().unwrap().push((px, py));
+                if y_seq.ival[idx].is_none() {
+                    continue; // no label for a filled value
+                }
+                let visible = x >= self.x_min && x <= self.x_max && axis.scale.contains(y);
+                if visible {
+                    last_point = Some((px, py));
+                    if min_point.is_none_or(|(min, _)| y < min) {
+                        min_point = Some((y, (px, py)));
                     }
-                    if self.hover {
-                        let mut point_group = node::element::Group::new().set("class", "inv");
-                        let circle = node::element::Circle::new()
-                            .set("fill", COLORS[seq_idx])
-                            .set("cx", x)
-                            .set("cy", y)
-                            .set("opacity", 0)
-                            .set("r", 8);
-                        point_group.append(circle);
-                        let mut point_opt_group = node::element::Group::new().set("class", "opt");
-                        let point_label_shadow = element::Text::new(&label)
-                            .set("x", x - 5)
-                            .set("y", y - 10)
-                            .set("stroke", "#222")
-                            .set("stroke-width", 5)
-                            .set("text-anchor", "end")
-                            .set("font-size", 8);
-                        point_opt_group.append(point_label_shadow);
-                        let circle = node::element::Circle::new()
-                            .set("fill", COLORS[seq_idx])
-                            .set("cx", x)
-                            .set("cy", y)
-                            .set("r", 4);
-                        point_opt_group.append(circle);
-                        let point_label = element::Text::new(label)
-                            .set("x", x - 5)
-                            .set("y", y - 10)
-                            .set("fill", TICK_LABEL_COLOR)
-                            .set("text-anchor", "end")
-                            .set("font-size", 8);
-                        point_opt_group.append(point_label);
-                        point_group.append(point_opt_group);
-                        points_group.append(point_group);
+                    if max_point.is_none_or(|(max, _)| y > max) {
+                        max_point = Some((y, (px, py)));
+                    }
+                }
+                if self.hover && visible {
+                    if let Some(x_label) = self.x_label(idx) {
+                        let label = format!("{}, {}", x_label, y_format.format(y));
+                        let point = self.hover_point(seq_idx, (px, py), &[label]);
+                        points_group.append(point.set("data-x", x));
                     }
                 }
             }
-            let curve = element::Path::new()
-                .set("fill", "none")
-                .set("stroke", COLORS[seq_idx])
-                .set("stroke-width", 3)
-                .set("opacity", 0.8)
-                .set("stroke-linejoin", "round")
-                .set("d", curve_data);
-            group.append(curve);
+            if let Some(last_point) = last_point {
+                last_points.push((seq_idx, last_point));
+            }
+            if self.options.extremes {
+                if let (Some((min, min_point)), Some((max, max_point))) = (min_point, max_point) {
+                    if max > min {
+                        let max_label = y_format.format(max);
+                        callouts_group
+                            .append(self.callout(seq_idx, panel, max_point, &max_label, true));
+                        let min_label = y_format.format(min);
+                        callouts_group
+                            .append(self.callout(seq_idx, panel, min_point, &min_label, false));
+                    }
+                }
+            }
+            let mut curve_data = element::path::Data::new();
+            let mut series_group = self.series_group(seq_idx);
+            let mut markers_group = node::element::Group::new().set("class", "markers");
+            for segment in &segments {
+                if segment.len() == 1 && style.marker.is_none() {
+                    // an isolated point wouldn't be visible as a path
+                    let dot = node::element::Circle::new()
+                        .set("fill", "currentColor")
+                        .set("cx", segment[0].0)
+                        .set("cy", segment[0].1)
+                        .set("opacity", style.opacity)
+                        .set("r", (style.width * 0.7).max(1.0));
+                    series_group.append(dot);
+                } else {
+                    curve_data = append_curve(curve_data, segment, style.interpolation);
+                }
+            }
+            if style.marker.is_some() {
+                // markers along the curve, not too close to each other
+                let spacing = self.scaled(36);
+                let mut last_marker_x: Option<i64> = None;
+                for &(px, py) in segments.iter().flatten() {
+                    if last_marker_x.is_none_or(|last_x| px - last_x >= spacing) {
+                        markers_group.append(self.marker(seq_idx, (px, py)));
+                        last_marker_x = Some(px);
+                    }
+                }
+            }
+            let curve = |color: &str| {
+                let path = element::Path::new()
+                    .set("class", "curve")
+                    .set("fill", "none")
+                    .set("stroke", color)
+                    .set("stroke-width", style.width)
+                    .set("opacity", style.opacity)
+                    .set("stroke-linejoin", "round")
+                    .set("d", curve_data.clone());
+                match &style.dash_array {
+                    Some(dash_array) => path.set("stroke-dasharray", dash_array.as_str()),
+                    None => path,
+                }
+            };
+            match (&self.options.above_color, *axis_idx == 0 && threshold) {
+                (Some(above_color), true) => {
+                    // the curve is drawn twice, each one only visible
+                    // on its side of the threshold
+                    series_group.append(
+                        node::element::Group::new()
+                            .set(
+                                "clip-path",
+                                format!("url(#{})", self.threshold_clip_id(panel_idx, true)),
+                            )
+                            .add(curve(above_color)),
+                    );
+                    series_group.append(
+                        node::element::Group::new()
+                            .set(
+                                "clip-path",
+                                format!("url(#{})", self.threshold_clip_id(panel_idx, false)),
+                            )
+                            .add(curve("currentColor")),
+                    );
+                }
+                _ => {
+                    series_group.append(curve("currentColor"));
+                }
+            }
+            series_group.append(markers_group);
+            curves_group.append(series_group);
+            points_groups.push(points_group);
+        }
+        group.append(curves_group);
+        group.append(callouts_group);
+        if self.options.direct_labels {
+            group.append(self.direct_labels_group(panel, &last_points));
+        }
+        for points_group in points_groups {
             group.append(points_group);
         }
         group
     }
+    /// the value of a remarkable point, written above or below it, but
+    /// always inside the plot
+    fn callout(
+        &self,
+        seq_idx: usize,
+        panel: &Panel,
+        (x, y): (i64, i64),
+        label: &str,
+        above: bool,
+    ) -> node::element::Group {
+        let mut group = self
+            .series_group(seq_idx)
+            .set("class", format!("callout {}", series_class(seq_idx)));
+        let gr = &panel.gr;
+        let ring = node::element::Circle::new()
+            .set("fill", "none")
+            .set("stroke", "currentColor")
+            .set("stroke-width", 1.5)
+            .set("cx", x)
+            .set("cy", y)
+            .set("r", 5);
+        group.append(ring);
+        // the label goes on the other side when there's no room
+        let label_y = if above && y - self.scaled(16) >= gr.top
+            || !above && y + self.scaled(16) > gr.bottom()
+        {
+            y - self.scaled(8)
+        } else {
+            y + self.scaled(15)
+        };
+        let half_width = self.scaled(label.len() as i64 * 5) / 2 + 2;
+        let (label_x, anchor) = if x - half_width < gr.left {
+            (x - 4, "start")
+        } else if x + half_width > gr.right() {
+            (x + 4, "end")
+        } else {
+            (x, "middle")
+        };
+        let label_shadow = self
+            .text(TextRole::Label, label)
+            .set("class", "shadow")
+            .set("x", label_x)
+            .set("y", label_y)
+            .set("stroke", self.theme.background)
+            .set("stroke-width", 4)
+            .set("text-anchor", anchor);
+        group.append(label_shadow);
+        let label = self
+            .text(TextRole::Label, label)
+            .set("x", label_x)
+            .set("y", label_y)
+            .set("fill", "currentColor")
+            .set("text-anchor", anchor);
+        group.append(label);
+        group
+    }
+    /// the names of the series, written right of the last point of their
+    /// curves, or right of the tick labels when they'd overlap them,
+    /// and moved vertically to avoid overlapping
+    fn direct_labels_group(
+        &self,
+        panel: &Panel,
+        last_points: &[(usize, (i64, i64))],
+    ) -> node::element::Group {
+        let mut group = node::element::Group::new().set("class", "direct-labels");
+        let gr = &panel.gr;
+        let ys: Vec<i64> = last_points.iter().map(|(_, (_, y))| *y).collect();
+        let label_ys = spread(&ys, self.scaled(11), gr.top + 5, gr.bottom() - 5).unwrap_or(ys);
+        let font_size = self.font_size(TextRole::Legend);
+        let has_right_ticks = panel.axes.iter().any(|axis| axis.side == Side::Right);
+        for ((seq_idx, (x, _)), label_y) in last_points.iter().zip(label_ys) {
+            let header = &self.tbl.y_seq(*seq_idx).header;
+            let mut label_x = x + self.scaled(7);
+            let width = text_width(header, font_size).ceil() as i64;
+            if has_right_ticks && label_x + width > gr.right() {
+                label_x = label_x.max(gr.right() + self.direct_labels_offset());
+            }
+            let label_shadow = self
+                .text(TextRole::Legend, header.as_str())
+                .set("class", "shadow")
+                .set("x", label_x)
+                .set("y", label_y + self.scaled(3))
+                .set("stroke", self.theme.background)
+                .set("stroke-width", 4)
+                .set("text-anchor", "start");
+            group.append(label_shadow);
+            let label = self
+                .text(TextRole::Legend, header.as_str())
+                .set("class", series_class(*seq_idx))
+                .set("color", self.color(*seq_idx))
+                .set("x", label_x)
+                .set("y", label_y + self.scaled(3))
+                .set("fill", "currentColor")
+                .set("text-anchor", "start");
+            group.append(label);
+        }
+        group
+    }
     fn graph_group(&self) -> node::element::Group {
-        let mut graph =
-            node::element::Group::new().set("font-family", "Arial, Helvetica, sans-serif");
-        graph.append(self.y_scale_group());
-        graph.append(self.x_ticks_group());
-        graph.append(self.curbs_group());
-        graph.append(self.legend_group());
+        let mut graph = node::element::Group::new().set("font-family", self.font_family());
+        for (panel_idx, panel) in self.panels.iter().enumerate() {
+            graph.append(self.panel_title_group(panel));
+            graph.append(self.stripes_group(panel));
+            graph.append(self.y_scale_group(panel));
+            graph.append(self.x_ticks_group(panel));
+            if let Some(stack) = &self.stack {
+                graph.append(self.stack_group(panel_idx, panel, stack));
+            } else if self.x_scale.is_some() {
+                graph.append(self.scatter_group(panel_idx, panel));
+            } else {
+                graph.append(self.curbs_group(panel_idx, panel));
+            }
+            graph.append(self.hlines_group(panel));
+            graph.append(self.events_group(panel));
+        }
+        if !self.options.facet {
+            graph.append(self.legend_group());
+        }
+        graph.append(self.captions_group());
         graph
     }
+    fn definitions(&self) -> node::element::Definitions {
+        let mut defs = node::element::Definitions::new();
+        for (panel_idx, panel) in self.panels.iter().enumerate() {
+            defs.append(self.plot_clip_path(panel_idx, panel));
+            if let Some(clip_paths) = self.threshold_clip_paths(panel_idx, panel) {
+                for clip_path in clip_paths {
+                    defs.append(clip_path);
+                }
+            }
+        }
+        defs
+    }
     pub fn build_svg(&self) -> Document {
         let (width, height) = (self.width as i64, self.height as i64);
-        Document::new()
-            .set("viewBox", (0, 0, width, height))
-            .set("style", DOCUMENT_STYLE)
-            //.add(node::element::Style::new(SVG_STYLE))
+        let background = node::element::Rectangle::new()
+            .set("class", "background")
+            .set("width", width)
+            .set("height", height)
+            .set("fill", self.theme.background);
+        let mut style = SVG_STYLE.to_string();
+        if let Some(font) = &self.options.embedded_font {
+            style.push_str(&font.css());
+        }
+        let mut document = Document::new().set("viewBox", (0, 0, width, height));
+        match self.options.sizing {
+            SvgSizing::Fit => {}
+            SvgSizing::Fixed => {
+                document = document.set("width", width).set("height", height);
+            }
+            SvgSizing::Responsive => {
+                document = document.set("width", "100%").set(
+                    "style",
+                    format!(
+                        "height: auto; min-width: {}px; max-width: {}px",
+                        (width as f64 * RESPONSIVE_MIN_RATIO).round(),
+                        (width as f64 * RESPONSIVE_MAX_RATIO).round(),
+                    ),
+                );
+            }
+        }
+        if let Some(aspect_ratio) = &self.options.aspect_ratio {
+            document = document.set("preserveAspectRatio", aspect_ratio.as_str());
+        }
+        document
+            .add(node::element::Style::new(style))
+            .add(self.definitions())
+            .add(background)
             .add(self.graph_group())
     }
     pub fn write_svg<W: io::Write>(&self, mut writer: W) -> Result<()> {
@@ -276,3 +1849,190 @@ impl Graph {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod graph_tests {
+    use {super::*, chrono::FixedOffset};
+
+    fn graph(csv: &str, options: GraphOptions) -> Graph {
+        let tbl = Tbl::from_raw(RawTbl::read(csv.as_bytes()).unwrap()).unwrap();
+        Graph::new(tbl, options)
+    }
+
+    fn svg(csv: &str, options: GraphOptions) -> String {
+        graph(csv, options).build_svg().to_string()
+    }
+
+    /// the ids defined in the svg
+    fn ids(svg: &str) -> Vec<&str> {
+        svg.split(" id=\"")
+            .skip(1)
+            .filter_map(|part| part.split('"').next())
+            .collect()
+    }
+
+    /// the number of parts of the curves of the svg
+    fn curve_segments(svg: &str) -> usize {
+        svg.lines()
+            .filter(|line| line.contains("class=\"curve\""))
+            .map(|line| line.matches('M').count())
+            .sum()
+    }
+
+    #[test]
+    fn test_tz_labels() {
+        let csv = "time,a\n2024-01-01T00:00:00Z,1\n2024-01-01T06:00:00Z,2\n";
+        let svg_utc = svg(csv, GraphOptions::default());
+        assert!(svg_utc.contains("2024-01-01T06:00:00Z"));
+        let options = GraphOptions {
+            tz: Some(Timezone::Fixed(FixedOffset::east_opt(5 * 3600).unwrap())),
+            ..GraphOptions::default()
+        };
+        let svg_tz = svg(csv, options);
+        assert!(svg_tz.contains("2024-01-01T11:00:00+05:00"));
+        assert!(!svg_tz.contains("2024-01-01T06:00:00Z"));
+    }
+    #[test]
+    fn test_theme_variants_ids() {
+        let csv = "x,a\n1,10\n2,20\n3,30\n";
+        let options = GraphOptions {
+            hlines: vec!["15".parse().unwrap()],
+            above_color: Some("red".to_string()),
+            ..GraphOptions::default()
+        };
+        let mut graph = graph(csv, options);
+        let dark = graph.build_svg().to_string();
+        graph.set_id_prefix("light-");
+        graph.set_theme(&LIGHT_THEME);
+        let light = graph.build_svg().to_string();
+        let (dark_ids, light_ids) = (ids(&dark), ids(&light));
+        assert_eq!(dark_ids.len(), 3);
+        assert!(light_ids.iter().all(|id| !dark_ids.contains(id)));
+        for id in light_ids {
+            assert!(light.contains(&format!("url(#{})", id)));
+        }
+    }
+    #[test]
+    fn test_svg_classes() {
+        let options = GraphOptions {
+            styles: vec!["b:color=#123456".parse().unwrap()],
+            ..GraphOptions::default()
+        };
+        let svg = svg("x,a,b\n1,10,5\n2,20,8\n3,30,2\n", options);
+        assert!(svg.contains("<style>"));
+        assert!(svg.contains("g.inv:hover g.opt"));
+        for class in [
+            "axis x-axis",
+            "axis y-axis",
+            "tick",
+            "legend",
+            "series-0",
+            "series-1",
+        ] {
+            assert!(
+                svg.contains(&format!("class=\"{}\"", class)),
+                "no {:?}",
+                class
+            );
+        }
+        // the elements of a series take the color of their group
+        let lines: Vec<&str> = svg.lines().collect();
+        let curve_idx = lines
+            .iter()
+            .position(|line| line.contains("class=\"curve\""))
+            .unwrap();
+        assert!(lines[curve_idx].contains("stroke=\"currentColor\""));
+        assert!(lines[curve_idx - 1].starts_with("<g class=\"series-0\" color=\"#"));
+        assert!(svg.contains("<g class=\"series-1\" color=\"#123456\">"));
+        assert!(!svg.contains("stroke=\"#123456\""));
+    }
+    #[test]
+    fn test_stack_hover_range() {
+        let csv = "x,a,b\n1,10,5\n2,20,8\n3,30,2\n4,10,1\n";
+        let hover_points = |x_min: Option<i64>| {
+            let options = GraphOptions {
+                mode: ChartMode::Stacked,
+                x_min,
+                ..GraphOptions::default()
+            };
+            svg(csv, options).matches("class=\"inv series-").count()
+        };
+        assert_eq!(hover_points(None), 8);
+        assert_eq!(hover_points(Some(3)), 4);
+    }
+    #[test]
+    fn test_gap_segments() {
+        let csv = "x,a\n1,10\n2,20\n3,\n4,40\n5,50\n20,30\n21,20\n";
+        assert_eq!(curve_segments(&svg(csv, GraphOptions::default())), 1);
+        let options = GraphOptions {
+            gaps: vec!["break".parse().unwrap()],
+            ..GraphOptions::default()
+        };
+        assert_eq!(curve_segments(&svg(csv, options)), 2);
+        let options = GraphOptions {
+            gaps: vec!["a=break".parse().unwrap()],
+            max_gap: Some(5),
+            ..GraphOptions::default()
+        };
+        assert_eq!(curve_segments(&svg(csv, options)), 3);
+    }
+    #[test]
+    fn test_direct_labels() {
+        // the labels are written right of the tick labels, in the margin
+        let csv = "x,short,a much longer name\n1,10,2000\n2,20,3000\n";
+        let options = GraphOptions {
+            direct_labels: true,
+            ..GraphOptions::default()
+        };
+        let graph = graph(csv, options);
+        let gr = graph.panels[0].gr;
+        let group = graph.graph_group().to_string();
+        let labels: Vec<&str> = group
+            .lines()
+            .skip_while(|line| !line.contains("direct-labels"))
+            .take_while(|line| !line.contains("</g>"))
+            .filter(|line| line.contains("<text") && !line.contains("shadow"))
+            .collect();
+        assert_eq!(labels.len(), 2);
+        let font_size = graph.font_size(TextRole::Legend);
+        for (label, name) in labels.iter().zip(["short", "a much longer name"]) {
+            assert!(label.contains("text-anchor=\"start\""));
+            let x: i64 = label
+                .split(" x=\"")
+                .nth(1)
+                .unwrap()
+                .split('"')
+                .next()
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert!(x >= gr.right() + graph.ticks_width(Side::Right).unwrap());
+            assert!(x as f64 + text_width(name, font_size) <= graph.width as f64);
+        }
+    }
+    #[test]
+    fn test_event_labels() {
+        // close events, at the left end with one starting before the
+        // range, and at the right end: the labels are moved apart but
+        // stay in the plot
+        let csv = "x,a\n0,10\n100,20\n";
+        let events = "time,label,end\n-5,a,20\n0,b,\n99,c,\n100,d,\n";
+        let options = GraphOptions {
+            events: Event::read(events.as_bytes()).unwrap(),
+            ..GraphOptions::default()
+        };
+        let graph = graph(csv, options);
+        let gr = graph.panels[0].gr;
+        let group = graph.events_group(&graph.panels[0]).to_string();
+        assert!(!group.contains("class=\"inv\""));
+        let mut xs: Vec<i64> = group
+            .lines()
+            .filter(|line| line.contains("<text") && !line.contains("shadow"))
+            .filter_map(|line| line.split(" x=\"").nth(1)?.split('"').next()?.parse().ok())
+            .collect();
+        assert_eq!(xs.len(), 4);
+        xs.sort_unstable();
+        assert!(xs.windows(2).all(|w| w[1] - w[0] >= graph.scaled(10)));
+        assert!(xs[0] >= gr.left && xs[3] <= gr.right());
+    }
+}
@@ -0,0 +1,242 @@
+/// a horizontal reference line, eg a SLO limit or a capacity
+#[derive(Debug, Clone, PartialEq)]
+pub struct HLine {
+    pub value: i64,
+    pub label: Option<String>,
+    pub color: Option<String>,
+}
+
+impl std::str::FromStr for HLine {
+    type Err = String;
+    /// parse `value[:label[:color]]`, the label possibly containing
+    /// colons: the last part is only taken as color when it looks like one
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (value, rest) = match s.split_once(':') {
+            Some((value, rest)) => (value, Some(rest)),
+            None => (s, None),
+        };
+        let value = value
+            .trim()
+            .parse()
+            .map_err(|_| format!("expected a line like \"200:p99 SLO:#f00\", got {:?}", s))?;
+        let (label, color) = match rest.and_then(|rest| rest.rsplit_once(':')) {
+            Some((label, color)) if color.is_empty() || looks_like_color(color) => {
+                (Some(label), Some(color))
+            }
+            _ => (rest, None),
+        };
+        let non_empty = |part: Option<&str>| part.filter(|p| !p.is_empty()).map(String::from);
+        Ok(Self {
+            value,
+            label: non_empty(label),
+            color: non_empty(color),
+        })
+    }
+}
+
+/// the named colors of CSS, sorted
+static NAMED_COLORS: &[&str] = &[
+    "aliceblue",
+    "antiquewhite",
+    "aqua",
+    "aquamarine",
+    "azure",
+    "beige",
+    "bisque",
+    "black",
+    "blanchedalmond",
+    "blue",
+    "blueviolet",
+    "brown",
+    "burlywood",
+    "cadetblue",
+    "chartreuse",
+    "chocolate",
+    "coral",
+    "cornflowerblue",
+    "cornsilk",
+    "crimson",
+    "cyan",
+    "darkblue",
+    "darkcyan",
+    "darkgoldenrod",
+    "darkgray",
+    "darkgreen",
+    "darkgrey",
+    "darkkhaki",
+    "darkmagenta",
+    "darkolivegreen",
+    "darkorange",
+    "darkorchid",
+    "darkred",
+    "darksalmon",
+    "darkseagreen",
+    "darkslateblue",
+    "darkslategray",
+    "darkslategrey",
+    "darkturquoise",
+    "darkviolet",
+    "deeppink",
+    "deepskyblue",
+    "dimgray",
+    "dimgrey",
+    "dodgerblue",
+    "firebrick",
+    "floralwhite",
+    "forestgreen",
+    "fuchsia",
+    "gainsboro",
+    "ghostwhite",
+    "gold",
+    "goldenrod",
+    "gray",
+    "green",
+    "greenyellow",
+    "grey",
+    "honeydew",
+    "hotpink",
+    "indianred",
+    "indigo",
+    "ivory",
+    "khaki",
+    "lavender",
+    "lavenderblush",
+    "lawngreen",
+    "lemonchiffon",
+    "lightblue",
+    "lightcoral",
+    "lightcyan",
+    "lightgoldenrodyellow",
+    "lightgray",
+    "lightgreen",
+    "lightgrey",
+    "lightpink",
+    "lightsalmon",
+    "lightseagreen",
+    "lightskyblue",
+    "lightslategray",
+    "lightslategrey",
+    "lightsteelblue",
+    "lightyellow",
+    "lime",
+    "limegreen",
+    "linen",
+    "magenta",
+    "maroon",
+    "mediumaquamarine",
+    "mediumblue",
+    "mediumorchid",
+    "mediumpurple",
+    "mediumseagreen",
+    "mediumslateblue",
+    "mediumspringgreen",
+    "mediumturquoise",
+    "mediumvioletred",
+    "midnightblue",
+    "mintcream",
+    "mistyrose",
+    "moccasin",
+    "navajowhite",
+    "navy",
+    "oldlace",
+    "olive",
+    "olivedrab",
+    "orange",
+    "orangered",
+    "orchid",
+    "palegoldenrod",
+    "palegreen",
+    "paleturquoise",
+    "palevioletred",
+    "papayawhip",
+    "peachpuff",
+    "peru",
+    "pink",
+    "plum",
+    "powderblue",
+    "purple",
+    "rebeccapurple",
+    "red",
+    "rosybrown",
+    "royalblue",
+    "saddlebrown",
+    "salmon",
+    "sandybrown",
+    "seagreen",
+    "seashell",
+    "sienna",
+    "silver",
+    "skyblue",
+    "slateblue",
+    "slategray",
+    "slategrey",
+    "snow",
+    "springgreen",
+    "steelblue",
+    "tan",
+    "teal",
+    "thistle",
+    "tomato",
+    "turquoise",
+    "violet",
+    "wheat",
+    "white",
+    "whitesmoke",
+    "yellow",
+    "yellowgreen",
+];
+
+/// whether the string is a hex color, a color function like `rgb(...)`,
+/// or a CSS named color
+fn looks_like_color(s: &str) -> bool {
+    if let Some(hex) = s.strip_prefix('#') {
+        return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+    if let Some(args) = ["rgb(", "rgba(", "hsl(", "hsla("]
+        .iter()
+        .find_map(|f| s.strip_prefix(f))
+    {
+        return args.strip_suffix(')').is_some_and(|args| {
+            args.chars()
+                .all(|c| c.is_ascii_alphanumeric() || " ,.%/".contains(c))
+        });
+    }
+    NAMED_COLORS
+        .binary_search(&s.to_ascii_lowercase().as_str())
+        .is_ok()
+}
+
+#[cfg(test)]
+mod hline_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hline() {
+        let hline: HLine = "200:p99 SLO:#f00".parse().unwrap();
+        assert_eq!(hline.value, 200);
+        assert_eq!(hline.label.as_deref(), Some("p99 SLO"));
+        assert_eq!(hline.color.as_deref(), Some("#f00"));
+        let hline: HLine = "-5::green".parse().unwrap();
+        assert_eq!(hline.value, -5);
+        assert_eq!(hline.label, None);
+        assert_eq!(hline.color.as_deref(), Some("green"));
+        let hline: HLine = "200:p99: SLO".parse().unwrap();
+        assert_eq!(hline.label.as_deref(), Some("p99: SLO"));
+        assert_eq!(hline.color, None);
+        let hline: HLine = "200:ratio 1:2:rgb(200, 0, 0)".parse().unwrap();
+        assert_eq!(hline.label.as_deref(), Some("ratio 1:2"));
+        assert_eq!(hline.color.as_deref(), Some("rgb(200, 0, 0)"));
+        let hline: HLine = "200:step:limit:".parse().unwrap();
+        assert_eq!(hline.label.as_deref(), Some("step:limit"));
+        assert_eq!(hline.color, None);
+        let hline: HLine = "200:#1".parse().unwrap();
+        assert_eq!(hline.label.as_deref(), Some("#1"));
+        let hline: HLine = "15:p99 SLO:critical".parse().unwrap();
+        assert_eq!(hline.label.as_deref(), Some("p99 SLO:critical"));
+        assert_eq!(hline.color, None);
+        let hline: HLine = "15:p99 SLO:DarkOrange".parse().unwrap();
+        assert_eq!(hline.label.as_deref(), Some("p99 SLO"));
+        assert_eq!(hline.color.as_deref(), Some("DarkOrange"));
+        assert!("high".parse::<HLine>().is_err());
+    }
+}
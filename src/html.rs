@@ -1,25 +1,142 @@
-use {anyhow::*, std::io, svg::Document};
+use {crate::*, anyhow::*, std::io, svg::Document};
 
 static CSS: &str = r#"
-html, body { margin:0; padding:0; overflow:hidden; }
-body { background:#222; }
-svg { position:absolute; top:5%; left:5%; width:90%; height:90%; }
-svg g.fad { pointer-events:all; }
-svg g.fad g.opt { opacity:.3; }
-svg g.fad:hover g.opt { opacity:1; }
-svg g.inv g.opt { display:none; }
-svg g.inv:hover g.opt { display:block; }
+html, body { margin:0; padding:0; }
 "#;
 
-pub fn write_embedded<W: io::Write>(mut w: W, svg: &Document) -> Result<()> {
+/// placement of a single graph
+static SINGLE_CSS: &str = r#"
+svg:not([width]) { position:absolute; top:5%; left:5%; width:90%; height:90%; }
+svg[width] { display:block; margin:5vh auto; }
+svg[width="100%"] { width:90%; }
+"#;
+
+/// placement of the graphs of a dashboard, and display of the points
+/// highlighted because the same x is hovered in another graph
+static DASHBOARD_CSS: &str = r#"
+.dashboard { display:grid; gap:2vw; padding:2vw; }
+.dashboard svg { display:block; width:100%; height:auto; }
+svg g.inv.hl g.opt { display:block; }
+svg g.fad.hl g.opt { opacity:1; }
+"#;
+
+/// when an element with a `data-x` is hovered, highlight the elements
+/// with the same `data-x` in the other graphs
+static HOVER_SYNC_JS: &str = r#"
+function highlight(x, source) {
+    document.querySelectorAll("svg .hl").forEach(e => e.classList.remove("hl"));
+    if (x === null) return;
+    document.querySelectorAll(`svg [data-x="${x}"]`).forEach(e => {
+        if (e.ownerSVGElement !== source) e.classList.add("hl");
+    });
+}
+document.querySelectorAll("svg [data-x]").forEach(e => {
+    e.addEventListener("mouseenter", () => highlight(e.dataset.x, e.ownerSVGElement));
+    e.addEventListener("mouseleave", () => highlight(null));
+});
+"#;
+
+/// display only the variant of the graph matching the color scheme
+/// preferred by the user
+static AUTO_THEME_CSS: &str = r#"
+svg.light { display:none; }
+@media (prefers-color-scheme: light) {
+    svg.dark { display:none; }
+    svg.light { display:block; }
+}
+"#;
+
+/// write a HTML page with the given style, its body being written
+/// by `write_body`
+fn write_page<W, F>(mut w: W, css: &str, write_body: F) -> Result<()>
+where
+    W: io::Write,
+    F: FnOnce(&mut W) -> Result<()>,
+{
     writeln!(w, "<!DOCTYPE HTML>")?;
     writeln!(w, "<html>")?;
     writeln!(w, "<head>")?;
-    writeln!(w, "<style type=text/css>{}</style>", CSS)?;
+    writeln!(w, "<style type=text/css>{}{}</style>", CSS, css)?;
     writeln!(w, "</head>")?;
     writeln!(w, "<body>")?;
-    svg::write(&mut w, svg)?;
+    write_body(&mut w)?;
     writeln!(w, "</body>")?;
     writeln!(w, "</html>")?;
     Ok(())
 }
+
+/// write a page displaying a single graph, possibly in several variants
+fn write_single_page<W: io::Write>(w: W, css: &str, svgs: &[&Document]) -> Result<()> {
+    let css = format!("{}{}", SINGLE_CSS, css);
+    write_page(w, &css, |w| {
+        for svg in svgs {
+            svg::write(&mut *w, *svg)?;
+        }
+        Ok(())
+    })
+}
+
+/// the background of the page, following the preferred color
+/// scheme in auto theme
+fn background_css(theme: ThemeName) -> String {
+    match theme {
+        ThemeName::Auto => format!(
+            "body {{ background:{}; }}\n@media (prefers-color-scheme: light) {{ body {{ background:{}; }} }}\n{}",
+            DARK_THEME.background, LIGHT_THEME.background, AUTO_THEME_CSS,
+        ),
+        _ => format!("body {{ background:{}; }}\n", theme.theme().background),
+    }
+}
+
+pub fn write_embedded<W: io::Write>(w: W, svg: &Document, theme: &Theme) -> Result<()> {
+    let css = format!("body {{ background:{}; }}\n", theme.background);
+    write_single_page(w, &css, &[svg])
+}
+
+/// write a page whose graph follows the color scheme preferred by
+/// the user, dark by default
+pub fn write_embedded_auto<W: io::Write>(
+    w: W,
+    dark_svg: &Document,
+    light_svg: &Document,
+) -> Result<()> {
+    let css = background_css(ThemeName::Auto);
+    let dark_svg = dark_svg.clone().set("class", "dark");
+    let light_svg = light_svg.clone().set("class", "light");
+    write_single_page(w, &css, &[&dark_svg, &light_svg])
+}
+
+/// write a page with several graphs in a grid, hovering a point
+/// of a graph showing the points of the same x in the other ones.
+/// Each graph comes with its light variant when the theme is auto
+pub fn write_dashboard<W: io::Write>(
+    w: W,
+    graphs: &[(Document, Option<Document>)],
+    columns: usize,
+    theme: ThemeName,
+) -> Result<()> {
+    let css = format!("{}{}", DASHBOARD_CSS, background_css(theme));
+    write_page(w, &css, |w| {
+        writeln!(
+            w,
+            "<div class=dashboard style=\"grid-template-columns: repeat({}, 1fr)\">",
+            columns,
+        )?;
+        for (svg, light_svg) in graphs {
+            writeln!(w, "<div>")?;
+            match light_svg {
+                Some(light_svg) => {
+                    svg::write(&mut *w, &svg.clone().set("class", "dark"))?;
+                    svg::write(&mut *w, &light_svg.clone().set("class", "light"))?;
+                }
+                None => {
+                    svg::write(&mut *w, svg)?;
+                }
+            }
+            writeln!(w, "</div>")?;
+        }
+        writeln!(w, "</div>")?;
+        writeln!(w, "<script>{}</script>", HOVER_SYNC_JS)?;
+        Ok(())
+    })
+}
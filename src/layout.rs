@@ -0,0 +1,116 @@
+/// the space around a plot, in pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Margins {
+    pub top: i64,
+    pub right: i64,
+    pub bottom: i64,
+    pub left: i64,
+}
+
+impl Margins {
+    pub const fn new(top: i64, right: i64, bottom: i64, left: i64) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+}
+
+impl std::str::FromStr for Margins {
+    type Err = String;
+    /// parse margins given as in CSS: "all", "vertical,horizontal",
+    /// or "top,right,bottom,left"
+    fn from_str(s: &str) -> Result<Self, String> {
+        let values: Vec<i64> = s
+            .split(',')
+            .map(|v| v.trim().parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("invalid margins: {:?}", s))?;
+        match values[..] {
+            [all] => Ok(Self::new(all, all, all, all)),
+            [vertical, horizontal] => Ok(Self::new(vertical, horizontal, vertical, horizontal)),
+            [top, right, bottom, left] => Ok(Self::new(top, right, bottom, left)),
+            _ => Err(format!("expected 1, 2 or 4 margins, got {:?}", s)),
+        }
+    }
+}
+
+/// the dimensions of the graph
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    pub width: usize,
+    pub height: usize,
+    /// when not set, margins depend on the chart mode and are
+    /// scaled with the text
+    pub margins: Option<Margins>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 500,
+            margins: None,
+        }
+    }
+}
+
+impl Layout {
+    /// the ratio applied to font sizes and to distances depending on
+    /// them, so that text is readable on big graphs while not taking
+    /// all the room of small ones
+    pub fn text_scale(&self) -> f64 {
+        let default = Self::default();
+        let area_ratio =
+            (self.width * self.height) as f64 / (default.width * default.height) as f64;
+        area_ratio.sqrt().clamp(0.75, 2.0)
+    }
+    pub fn font_size(&self, base: f64) -> f64 {
+        (base * self.text_scale() * 10.0).round() / 10.0
+    }
+    /// scale a distance related to text
+    pub fn scaled(&self, distance: i64) -> i64 {
+        (distance as f64 * self.text_scale()).round() as i64
+    }
+    /// the margins to use, either the ones explicitely set or
+    /// the given default ones, scaled
+    pub fn margins(&self, default: Margins) -> Margins {
+        self.margins.unwrap_or_else(|| {
+            Margins::new(
+                self.scaled(default.top),
+                self.scaled(default.right),
+                self.scaled(default.bottom),
+                self.scaled(default.left),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod layout_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_margins() {
+        assert_eq!("10".parse(), Ok(Margins::new(10, 10, 10, 10)));
+        assert_eq!("10, 20".parse(), Ok(Margins::new(10, 20, 10, 20)));
+        assert_eq!("1,2,3,4".parse(), Ok(Margins::new(1, 2, 3, 4)));
+        assert!("1,2,3".parse::<Margins>().is_err());
+    }
+    #[test]
+    fn test_text_scale() {
+        assert_eq!(Layout::default().text_scale(), 1.0);
+        let big = Layout {
+            width: 1600,
+            height: 1000,
+            margins: None,
+        };
+        assert_eq!(big.font_size(8.0), 16.0);
+        assert_eq!(
+            big.margins(Margins::new(1, 2, 3, 4)),
+            Margins::new(2, 4, 6, 8)
+        );
+    }
+}
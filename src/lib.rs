@@ -2,23 +2,47 @@
 extern crate log;
 
 mod app;
+mod axis;
 mod cli;
+mod curve;
+mod dashboard;
+mod event;
+mod font;
 mod graph;
+mod hline;
 mod html;
+mod layout;
 mod nature;
+mod number_format;
+mod options;
+mod palette;
+mod panel;
+mod pattern;
 mod projector;
 mod raw;
 mod rect;
 mod scale;
 mod seq;
+mod series_setting;
+mod sizing;
 mod skin;
+mod stack;
+mod stats;
+mod stripes;
+mod style;
 mod tbl;
+mod text_width;
+mod timezone;
+mod trend;
 mod unoverlap;
 mod visibility;
 
 pub use {
-    app::*, cli::*, graph::*, html::*, nature::*, projector::*, raw::*, rect::*, scale::*, seq::*,
-    skin::*, tbl::*, unoverlap::*, visibility::*,
+    app::*, axis::*, cli::*, curve::*, dashboard::*, event::*, font::*, graph::*, hline::*,
+    html::*, layout::*, nature::*, number_format::*, options::*, palette::*, panel::*, pattern::*,
+    projector::*, raw::*, rect::*, scale::*, seq::*, series_setting::*, sizing::*, skin::*,
+    stack::*, stats::*, stripes::*, style::*, tbl::*, text_width::*, timezone::*, trend::*,
+    unoverlap::*, visibility::*,
 };
 
 // Reexport svg crate
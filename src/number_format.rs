@@ -0,0 +1,154 @@
+/// the way a number is written in ticks and hover labels
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NumberFormat {
+    /// the number, as is: 3000000000
+    #[default]
+    Plain,
+    /// with thousands separators: 3,000,000,000
+    Thousands,
+    /// with a SI prefix: 3G
+    Si,
+    /// as a quantity of bytes, with binary units: 2.8 GiB
+    Bytes,
+    /// as a percentage: 42%
+    Percent,
+    /// with a fixed number of decimals: 42.00
+    Fixed(usize),
+}
+
+static SI_PREFIXES: &[&str] = &["", "k", "M", "G", "T", "P", "E"];
+static BINARY_UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+impl NumberFormat {
+    pub fn format(self, v: i64) -> String {
+        match self {
+            Self::Plain => v.to_string(),
+            Self::Thousands => with_thousands_separators(v),
+            Self::Si => {
+                let (m, idx) = reduce(v as f64, 1000.0);
+                format!("{}{}", short(m), SI_PREFIXES[idx])
+            }
+            Self::Bytes => {
+                let (m, idx) = reduce(v as f64, 1024.0);
+                format!("{} {}", short(m), BINARY_UNITS[idx])
+            }
+            Self::Percent => format!("{}%", v),
+            Self::Fixed(decimals) => format!("{:.*}", decimals, v as f64),
+        }
+    }
+}
+
+/// divide the value by the base until it's small enough, return
+/// the reduced value and the number of divisions.
+/// The check is done on the rounded value, so that 999,999 doesn't
+/// become "1000k"
+fn reduce(mut v: f64, base: f64) -> (f64, usize) {
+    let mut idx = 0;
+    while round_short(v).abs() >= base && idx + 1 < SI_PREFIXES.len() {
+        v /= base;
+        idx += 1;
+    }
+    (v, idx)
+}
+
+/// round a reduced value the way it's written: with one decimal
+/// when it's small
+fn round_short(v: f64) -> f64 {
+    if v.abs() < 10.0 {
+        (v * 10.0).round() / 10.0
+    } else {
+        v.round()
+    }
+}
+
+/// write a reduced value with one decimal when it's small, and
+/// no useless trailing zero
+fn short(v: f64) -> String {
+    let s = if v.abs() < 10.0 {
+        format!("{:.1}", v)
+    } else {
+        format!("{:.0}", v)
+    };
+    match s.strip_suffix(".0") {
+        Some(s) => s.to_string(),
+        None => s,
+    }
+}
+
+fn with_thousands_separators(v: i64) -> String {
+    let digits = v.unsigned_abs().to_string();
+    let mut s = String::with_capacity(digits.len() * 4 / 3 + 1);
+    if v < 0 {
+        s.push('-');
+    }
+    for (idx, c) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+            s.push(',');
+        }
+        s.push(c);
+    }
+    s
+}
+
+impl std::str::FromStr for NumberFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        let s = s.to_lowercase();
+        if let Some(decimals) = s.strip_prefix("fixed:") {
+            return decimals
+                .parse()
+                .map(Self::Fixed)
+                .map_err(|_| format!("invalid number of decimals: {:?}", decimals));
+        }
+        match s.as_ref() {
+            "plain" => Ok(Self::Plain),
+            "thousands" => Ok(Self::Thousands),
+            "si" => Ok(Self::Si),
+            "bytes" => Ok(Self::Bytes),
+            "percent" | "%" => Ok(Self::Percent),
+            "fixed" => Ok(Self::Fixed(2)),
+            _ => Err(format!("unrecognized number format {:?}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod number_format_tests {
+    use super::*;
+
+    #[test]
+    fn test_si() {
+        assert_eq!(NumberFormat::Si.format(3_000_000_000), "3G");
+        assert_eq!(NumberFormat::Si.format(1_500), "1.5k");
+        assert_eq!(NumberFormat::Si.format(123_456), "123k");
+        assert_eq!(NumberFormat::Si.format(-42), "-42");
+        assert_eq!(NumberFormat::Si.format(999), "999");
+        assert_eq!(NumberFormat::Si.format(999_999), "1M");
+        assert_eq!(NumberFormat::Si.format(-999_999), "-1M");
+        assert_eq!(NumberFormat::Si.format(999_499), "999k");
+    }
+    #[test]
+    fn test_bytes() {
+        assert_eq!(NumberFormat::Bytes.format(3_000_000_000), "2.8 GiB");
+        assert_eq!(NumberFormat::Bytes.format(512), "512 B");
+        assert_eq!(NumberFormat::Bytes.format(2048), "2 KiB");
+        assert_eq!(NumberFormat::Bytes.format(1023), "1023 B");
+        assert_eq!(NumberFormat::Bytes.format(1_048_575), "1 MiB");
+    }
+    #[test]
+    fn test_thousands() {
+        assert_eq!(
+            NumberFormat::Thousands.format(3_000_000_000),
+            "3,000,000,000"
+        );
+        assert_eq!(NumberFormat::Thousands.format(-12_345), "-12,345");
+        assert_eq!(NumberFormat::Thousands.format(999), "999");
+    }
+    #[test]
+    fn test_parse() {
+        assert_eq!("SI".parse(), Ok(NumberFormat::Si));
+        assert_eq!("fixed:3".parse(), Ok(NumberFormat::Fixed(3)));
+        assert_eq!(NumberFormat::Fixed(1).format(42), "42.0");
+        assert!("whatever".parse::<NumberFormat>().is_err());
+    }
+}
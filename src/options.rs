@@ -0,0 +1,108 @@
+use crate::*;
+
+/// options defining how the graph is built
+#[derive(Debug, Clone, Default)]
+pub struct GraphOptions {
+    /// size of the graph, and margins around the plot
+    pub layout: Layout,
+    /// how the SVG is sized in the page or viewer
+    pub sizing: SvgSizing,
+    /// how the graph is placed in a box of a different ratio
+    pub aspect_ratio: Option<AspectRatio>,
+    /// colors of the graph
+    pub theme: ThemeName,
+    /// colors of the series
+    pub palette: PaletteName,
+    /// whether series are also distinguished by dash patterns
+    /// and marker shapes, for colorblind readers and monochrome prints
+    pub patterns: bool,
+    /// per series overrides of the look of the series, by glob
+    pub styles: Vec<StyleRule>,
+    /// fonts of the texts, by role
+    pub fonts: Vec<FontSetting>,
+    /// a font file to embed in the SVG
+    pub embedded_font: Option<EmbeddedFont>,
+    /// format of the x values, when they're numbers
+    pub x_format: NumberFormat,
+    /// format of the y ticks, and of the y values when not
+    /// overridden by a series format
+    pub y_format: NumberFormat,
+    /// per series overrides of the y format
+    pub series_formats: Vec<SeriesSetting<NumberFormat>>,
+    /// forced bounds of the x axis
+    pub x_min: Option<i64>,
+    pub x_max: Option<i64>,
+    /// forced bounds of the y axis
+    pub y_min: Option<i64>,
+    pub y_max: Option<i64>,
+    /// whether the y axis must include zero
+    pub zero: ZeroPolicy,
+    /// headers of the series which must use the left axis
+    pub left_axis: Vec<String>,
+    /// whether series of very different magnitudes should be
+    /// automatically dispatched on the left and right axes
+    pub auto_axes: bool,
+    /// whether to draw one small panel per y series instead
+    /// of drawing them all together
+    pub facet: bool,
+    /// whether facet panels share the same y scale
+    pub shared_y: bool,
+    /// how the y series are drawn
+    pub mode: ChartMode,
+    /// how missing values are handled in stacks
+    pub stack_missing: MissingPolicy,
+    /// whether to draw least-squares trend lines in scatter mode
+    pub trend: bool,
+    /// how the points of the series are joined
+    pub interpolations: Vec<SeriesSetting<Interpolation>>,
+    /// how the curves go over missing values
+    pub gaps: Vec<SeriesSetting<GapPolicy>>,
+    /// the distance between two consecutive x (in milliseconds for
+    /// dates) beyond which curves are interrupted
+    pub max_gap: Option<i64>,
+    /// timezone in which dates are displayed and days computed,
+    /// defaulting to the one of the x column
+    pub tz: Option<Timezone>,
+    /// period of the alternating background bands of a time axis
+    pub stripes: Option<StripePeriod>,
+    /// whether to shade the weekends of a time axis
+    pub weekends: bool,
+    /// hours of the day to shade on a time axis
+    pub night_hours: Option<HourRange>,
+    /// events displayed over the curves
+    pub events: Vec<Event>,
+    /// horizontal reference lines, against the main y axis
+    pub hlines: Vec<HLine>,
+    /// whether the main y axis must include the reference lines
+    pub hlines_in_scale: bool,
+    /// color of the parts of the curves above the first reference line
+    pub above_color: Option<String>,
+    /// whether to write the name of each series at the end of its curve
+    pub direct_labels: bool,
+    /// whether to mark the min and max of each series with their values
+    pub extremes: bool,
+    /// whether the legend shows statistics of the series
+    pub legend_stats: bool,
+    /// title written over the graph
+    pub title: Option<String>,
+    /// smaller text written under the title
+    pub subtitle: Option<String>,
+    /// caption of the x axis, defaulting to the header of the x column
+    /// (an empty string removing it)
+    pub x_label: Option<String>,
+    /// caption of the main y axis
+    pub y_label: Option<String>,
+}
+
+impl GraphOptions {
+    /// return the format to use for the values of the series
+    pub fn series_format(&self, header: &str) -> NumberFormat {
+        series_setting(&self.series_formats, header).unwrap_or(self.y_format)
+    }
+    pub fn series_gaps(&self, header: &str) -> GapPolicy {
+        series_setting(&self.gaps, header).unwrap_or_default()
+    }
+    pub fn series_interpolation(&self, header: &str) -> Interpolation {
+        series_setting(&self.interpolations, header).unwrap_or_default()
+    }
+}
@@ -0,0 +1,170 @@
+use crate::*;
+
+/// chroma of generated colors, close to the one of the hand picked ones
+static CHROMA: f64 = 0.12;
+
+/// the first hue, in degrees, a salmon close to the first hand picked color
+static START_HUE: f64 = 40.0;
+
+/// the colors of the Okabe–Ito palette, distinguishable with the
+/// common color vision deficiencies, without the last one, black,
+/// which is replaced by the text color of the theme
+static OKABE_ITO: &[&str] = &[
+    "#e69f00", "#56b4e9", "#009e73", "#f0e442", "#0072b2", "#d55e00", "#cc79a7",
+];
+
+/// evenly spaced stops of the viridis color map
+static VIRIDIS: [[u8; 3]; 9] = [
+    [0x44, 0x01, 0x54],
+    [0x48, 0x28, 0x78],
+    [0x3e, 0x49, 0x89],
+    [0x31, 0x68, 0x8e],
+    [0x26, 0x82, 0x8e],
+    [0x1f, 0x9e, 0x89],
+    [0x35, 0xb7, 0x79],
+    [0x6e, 0xce, 0x58],
+    [0xfd, 0xe7, 0x25],
+];
+
+/// the set of colors given to the series
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PaletteName {
+    /// the colors of the theme, completed with generated ones
+    #[default]
+    Default,
+    /// the Okabe–Ito palette, colorblind safe, colors being
+    /// reused when there are more than 8 series
+    OkabeIto,
+    /// colors sampled along the viridis color map, colorblind safe
+    /// and readable in grayscale
+    Viridis,
+}
+
+impl std::str::FromStr for PaletteName {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_ref() {
+            "default" => Ok(Self::Default),
+            "okabe-ito" | "okabeito" | "okabe_ito" => Ok(Self::OkabeIto),
+            "viridis" => Ok(Self::Viridis),
+            _ => Err(format!("unrecognized palette {:?}", s)),
+        }
+    }
+}
+
+/// the color at position `t`, in [0, 1], of the viridis color map
+fn viridis(t: f64) -> String {
+    let pos = t.clamp(0.0, 1.0) * (VIRIDIS.len() - 1) as f64;
+    let idx = (pos.floor() as usize).min(VIRIDIS.len() - 2);
+    let ratio = pos - idx as f64;
+    let mut hex = String::from("#");
+    for (a, b) in VIRIDIS[idx].iter().zip(&VIRIDIS[idx + 1]) {
+        let c = *a as f64 + (*b as f64 - *a as f64) * ratio;
+        hex.push_str(&format!("{:02x}", c.round() as u8));
+    }
+    hex
+}
+
+/// convert a component of linear sRGB to its gamma encoded value
+fn gamma(x: f64) -> f64 {
+    if x <= 0.0031308 {
+        12.92 * x
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// convert a color given in the OKLCH space (lightness in [0, 1],
+/// chroma, and hue in degrees) to a "#rrggbb" sRGB color. Colors out
+/// of the sRGB gamut are clipped
+pub fn oklch_to_hex(lightness: f64, chroma: f64, hue: f64) -> String {
+    let hue = hue.to_radians();
+    let (a, b) = (chroma * hue.cos(), chroma * hue.sin());
+    let l = (lightness + 0.396_337_777_4 * a + 0.215_803_757_3 * b).powi(3);
+    let m = (lightness - 0.105_561_345_8 * a - 0.063_854_172_8 * b).powi(3);
+    let s = (lightness - 0.089_484_177_5 * a - 1.291_485_548_0 * b).powi(3);
+    let rgb = [
+        4.076_741_662_1 * l - 3.307_711_591_3 * m + 0.230_969_929_2 * s,
+        -1.268_438_004_6 * l + 2.609_757_401_1 * m - 0.341_319_396_5 * s,
+        -0.004_196_086_3 * l - 0.703_418_614_7 * m + 1.707_614_701_0 * s,
+    ];
+    let mut hex = String::from("#");
+    for x in rgb {
+        let x = (gamma(x.clamp(0.0, 1.0)) * 255.0).round() as u8;
+        hex.push_str(&format!("{:02x}", x));
+    }
+    hex
+}
+
+/// generate `count` colors with evenly spaced hues in a perceptual
+/// color space, alternating lightnesses so that neighbours differ more
+pub fn generated_palette(count: usize, lightnesses: [f64; 2]) -> Vec<String> {
+    (0..count)
+        .map(|idx| {
+            let hue = START_HUE + 360.0 * idx as f64 / count as f64;
+            oklch_to_hex(lightnesses[idx % 2], CHROMA, hue)
+        })
+        .collect()
+}
+
+/// the colors of `count` series
+pub fn series_colors(theme: &Theme, palette: PaletteName, count: usize) -> Vec<String> {
+    match palette {
+        PaletteName::Default if count <= theme.palette.len() => theme.palette[..count]
+            .iter()
+            .map(|c| c.to_string())
+            .collect(),
+        PaletteName::Default => generated_palette(count, theme.generated_lightnesses),
+        PaletteName::OkabeIto => OKABE_ITO
+            .iter()
+            .copied()
+            .chain(std::iter::once(theme.text))
+            .cycle()
+            .take(count)
+            .map(String::from)
+            .collect(),
+        PaletteName::Viridis => {
+            // the darkest end is invisible on a dark background, and
+            // the lightest one on a light background
+            let (start, end) = if theme.dark { (0.35, 1.0) } else { (0.0, 0.85) };
+            let step = (end - start) / (count.max(2) - 1) as f64;
+            (0..count)
+                .map(|idx| viridis(start + step * idx as f64))
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod palette_tests {
+    use super::*;
+
+    #[test]
+    fn test_oklch_to_hex() {
+        assert_eq!(oklch_to_hex(1.0, 0.0, 0.0), "#ffffff");
+        assert_eq!(oklch_to_hex(0.0, 0.0, 123.0), "#000000");
+        assert_eq!(oklch_to_hex(0.628, 0.2577, 29.23), "#ff0000");
+    }
+    #[test]
+    fn test_series_colors() {
+        assert_eq!(
+            series_colors(&DARK_THEME, PaletteName::Default, 3)[0],
+            COLORS[0]
+        );
+        let colors = series_colors(&LIGHT_THEME, PaletteName::Default, 40);
+        assert_eq!(colors.len(), 40);
+        for (idx, color) in colors.iter().enumerate() {
+            assert!(!colors[idx + 1..].contains(color));
+        }
+        let colors = series_colors(&DARK_THEME, PaletteName::OkabeIto, 9);
+        assert_eq!(colors[7], DARK_THEME.text);
+        assert_eq!(colors[8], colors[0]);
+    }
+    #[test]
+    fn test_viridis() {
+        assert_eq!(viridis(0.0), "#440154");
+        assert_eq!(viridis(1.0), "#fde725");
+        let colors = series_colors(&LIGHT_THEME, PaletteName::Viridis, 5);
+        assert_eq!(colors[0], "#440154");
+    }
+}
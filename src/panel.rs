@@ -0,0 +1,85 @@
+use crate::*;
+
+/// a plot area, drawing some of the y series against its own
+/// y axes, in a given rect of the graph
+pub struct Panel {
+    pub gr: IntRect,
+    pub title: Option<String>,
+    pub axes: Vec<YAxis>,            // the first one is the main axis
+    pub series: Vec<(usize, usize)>, // (index of the y seq, index of its axis)
+    pub x_labels: bool,              // whether to write the x tick labels below the plot
+}
+
+impl Panel {
+    /// build a panel for the given y series, each one with the side of its axis.
+    ///
+    /// When `shared_y` is given, it's used as data range of all axes instead
+    /// of the range of the panel's series
+    pub fn new(
+        tbl: &Tbl,
+        options: &GraphOptions,
+        gr: IntRect,
+        x_range: (i64, i64),
+        series_sides: &[(usize, Side)],
+        shared_y: Option<(i64, i64)>,
+    ) -> Self {
+        let (x_min, x_max) = x_range;
+        let mut axes = Vec::new();
+        let mut series = Vec::new();
+        for side in [Side::Right, Side::Left] {
+            let range = tbl.y_min_max_where(|idx| series_sides.contains(&(idx, side)));
+            let (mut y_min, mut y_max) = match (shared_y, range) {
+                (_, None) => {
+                    continue;
+                }
+                (Some(shared_y), _) => shared_y,
+                (None, Some(range)) => range,
+            };
+            // forced bounds and reference lines only apply to the main axis
+            let (forced_min, forced_max) = if axes.is_empty() {
+                if options.hlines_in_scale {
+                    for hline in &options.hlines {
+                        y_min = y_min.min(hline.value);
+                        y_max = y_max.max(hline.value);
+                    }
+                }
+                (options.y_min, options.y_max)
+            } else {
+                (None, None)
+            };
+            let scale = Scale::with_bounds(y_min, y_max, options.zero, forced_min, forced_max);
+            let sr = IntRect::new(x_min, scale.max, x_max - x_min, -scale.range());
+            let projector = Projector::new(&sr, &gr);
+            for (seq_idx, seq_side) in series_sides {
+                if *seq_side == side {
+                    series.push((*seq_idx, axes.len()));
+                }
+            }
+            axes.push(YAxis {
+                side,
+                scale,
+                projector,
+            });
+        }
+        series.sort_unstable();
+        Self {
+            gr,
+            title: None,
+            axes,
+            series,
+            x_labels: true,
+        }
+    }
+    pub fn project_x(&self, x: i64) -> i64 {
+        self.axes[0].projector.project_x(x)
+    }
+    /// return the axis of the y seq, assuming it's drawn in this panel
+    pub fn seq_axis(&self, seq_idx: usize) -> &YAxis {
+        let axis_idx = self
+            .series
+            .iter()
+            .find(|(idx, _)| *idx == seq_idx)
+            .map_or(0, |(_, axis_idx)| *axis_idx);
+        &self.axes[axis_idx]
+    }
+}
@@ -0,0 +1,138 @@
+use svg::node::element::path::Data;
+
+/// the shape of the markers of a series
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarkerShape {
+    Circle,
+    Square,
+    Triangle,
+    Diamond,
+    InvertedTriangle,
+    Cross,
+}
+
+impl std::str::FromStr for MarkerShape {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_ref() {
+            "circle" | "dot" => Ok(Self::Circle),
+            "square" => Ok(Self::Square),
+            "triangle" => Ok(Self::Triangle),
+            "diamond" => Ok(Self::Diamond),
+            "inverted-triangle" | "triangle-down" => Ok(Self::InvertedTriangle),
+            "cross" | "plus" => Ok(Self::Cross),
+            _ => Err(format!("unrecognized marker shape {:?}", s)),
+        }
+    }
+}
+
+static MARKER_SHAPES: &[MarkerShape] = &[
+    MarkerShape::Circle,
+    MarkerShape::Square,
+    MarkerShape::Triangle,
+    MarkerShape::Diamond,
+    MarkerShape::InvertedTriangle,
+    MarkerShape::Cross,
+];
+
+/// dash patterns of the curves, from solid to more and more broken
+static DASH_ARRAYS: &[Option<&str>] = &[
+    None,
+    Some("9 4"),
+    Some("2 4"),
+    Some("9 3 2 3"),
+    Some("14 4 2 4 2 4"),
+    Some("5 5"),
+];
+
+impl MarkerShape {
+    /// the outline of a marker centered on `(x, y)`, whose size is
+    /// about the one of a circle of radius `r`
+    pub fn data(self, (x, y): (i64, i64), r: f64) -> Data {
+        let (x, y) = (x as f64, y as f64);
+        match self {
+            Self::Circle => Data::new()
+                .move_to((x - r, y))
+                .elliptical_arc_to((r, r, 0, 1, 0, x + r, y))
+                .elliptical_arc_to((r, r, 0, 1, 0, x - r, y))
+                .close(),
+            Self::Square => {
+                let r = r * 0.9;
+                Data::new()
+                    .move_to((x - r, y - r))
+                    .line_to((x + r, y - r))
+                    .line_to((x + r, y + r))
+                    .line_to((x - r, y + r))
+                    .close()
+            }
+            Self::Triangle | Self::InvertedTriangle => {
+                let (r, dir) = (r * 1.3, if self == Self::Triangle { 1.0 } else { -1.0 });
+                Data::new()
+                    .move_to((x, y - r * dir))
+                    .line_to((x + r * 0.87, y + r * 0.5 * dir))
+                    .line_to((x - r * 0.87, y + r * 0.5 * dir))
+                    .close()
+            }
+            Self::Diamond => {
+                let r = r * 1.25;
+                Data::new()
+                    .move_to((x, y - r))
+                    .line_to((x + r, y))
+                    .line_to((x, y + r))
+                    .line_to((x - r, y))
+                    .close()
+            }
+            Self::Cross => {
+                let (r, t) = (r * 1.2, r * 0.4);
+                Data::new()
+                    .move_to((x - t, y - r))
+                    .line_to((x + t, y - r))
+                    .line_to((x + t, y - t))
+                    .line_to((x + r, y - t))
+                    .line_to((x + r, y + t))
+                    .line_to((x + t, y + t))
+                    .line_to((x + t, y + r))
+                    .line_to((x - t, y + r))
+                    .line_to((x - t, y + t))
+                    .line_to((x - r, y + t))
+                    .line_to((x - r, y - t))
+                    .line_to((x - t, y - t))
+                    .close()
+            }
+        }
+    }
+}
+
+/// what, besides the color, distinguishes a series
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeriesPattern {
+    /// the stroke-dasharray of the curve, none for a solid one
+    pub dash_array: Option<&'static str>,
+    pub marker: MarkerShape,
+}
+
+impl SeriesPattern {
+    /// the pattern of the series at the given index. Dashes and
+    /// markers are combined so that the first 36 patterns all differ
+    pub fn new(seq_idx: usize) -> Self {
+        let (dashes, markers) = (DASH_ARRAYS.len(), MARKER_SHAPES.len());
+        Self {
+            dash_array: DASH_ARRAYS[seq_idx % dashes],
+            marker: MARKER_SHAPES[(seq_idx + seq_idx / dashes) % markers],
+        }
+    }
+}
+
+#[cfg(test)]
+mod pattern_tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns_differ() {
+        let patterns: Vec<SeriesPattern> = (0..36).map(SeriesPattern::new).collect();
+        for (idx, pattern) in patterns.iter().enumerate() {
+            assert!(!patterns[idx + 1..].contains(pattern));
+        }
+        assert_eq!(SeriesPattern::new(0).dash_array, None);
+    }
+}
@@ -32,6 +32,9 @@ impl Projector {
     pub fn project_y(&self, y: i64) -> i64 {
         self.dy + (((y - self.sy) as f64) * self.ry) as i64
     }
+    pub fn project_yf(&self, y: f64) -> i64 {
+        self.dy + ((y - self.sy as f64) * self.ry).round() as i64
+    }
     pub fn project_point(&self, p: (i64, i64)) -> (i64, i64) {
         (self.project_x(p.0), self.project_y(p.1))
     }
@@ -1,5 +1,6 @@
 use num_traits::Num;
 
+#[derive(Debug, Clone, Copy)]
 pub struct Rect<N: Num + Copy> {
     pub left: N,
     pub top: N,
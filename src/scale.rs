@@ -1,3 +1,25 @@
+/// whether the scale must include zero
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ZeroPolicy {
+    Always,
+    Never,
+    /// include zero when it doesn't squash the values too much
+    #[default]
+    Auto,
+}
+
+impl std::str::FromStr for ZeroPolicy {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_ref() {
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            "auto" => Ok(Self::Auto),
+            _ => Err(format!("unrecognized zero policy {:?}", s)),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Scale {
     pub min: i64,
@@ -5,31 +27,115 @@ pub struct Scale {
     pub ticks: Vec<i64>,
 }
 impl Scale {
-    pub fn new(mut min: i64, mut max: i64) -> Self {
+    pub fn new(min: i64, max: i64) -> Self {
+        Self::with_bounds(min, max, ZeroPolicy::Auto, None, None)
+    }
+    /// build a scale containing the values between min and max, unless
+    /// the bounds are forced to other values
+    pub fn with_bounds(
+        mut min: i64,
+        mut max: i64,
+        zero: ZeroPolicy,
+        forced_min: Option<i64>,
+        forced_max: Option<i64>,
+    ) -> Self {
         debug_assert!(min <= max);
-        if max < min + 3 {
-            max += 2;
-            min -= 2;
+        if let Some(forced_min) = forced_min {
+            min = forced_min;
+            max = max.max(min);
         }
-        if min > 0 && (max - min) * 4 > max {
-            min = 0;
+        if let Some(forced_max) = forced_max {
+            max = forced_max;
+            if forced_min.is_none() {
+                min = min.min(max);
+            }
+        }
+        match zero {
+            ZeroPolicy::Always => {
+                if forced_min.is_none() {
+                    min = min.min(0);
+                }
+                if forced_max.is_none() {
+                    max = max.max(0);
+                }
+            }
+            ZeroPolicy::Auto => {
+                if forced_min.is_none() && min > 0 && (max - min) * 4 > max {
+                    min = 0;
+                }
+            }
+            ZeroPolicy::Never => {}
+        }
+        if max < min + 3 {
+            if forced_max.is_none() {
+                max += 2;
+            }
+            if forced_min.is_none() {
+                min -= 2;
+            }
         }
         let l = ((max - min) as f64).log10().floor() as u32;
         let d = 10i64.pow(l);
-        min = (min / d) * d;
-        let mut tick = min;
+        let mut tick = if forced_min.is_some() {
+            // the first tick is the first round value in the range
+            (min + d - 1).div_euclid(d) * d
+        } else {
+            min = min.div_euclid(d) * d;
+            min
+        };
         let mut ticks = vec![tick];
         loop {
             tick += d;
+            if forced_max.is_some() && tick > max {
+                break;
+            }
             ticks.push(tick);
             if tick > max {
                 break;
             }
         }
-        max = ticks[ticks.len() - 1];
+        if forced_max.is_none() {
+            max = ticks[ticks.len() - 1];
+        }
         Self { min, max, ticks }
     }
     pub fn range(&self) -> i64 {
         self.max - self.min
     }
+    pub fn contains(&self, v: i64) -> bool {
+        self.min <= v && v <= self.max
+    }
+}
+
+#[cfg(test)]
+mod scale_tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_zero() {
+        let scale = Scale::new(120, 470);
+        assert_eq!(scale.min, 0);
+        assert_eq!(scale.max, 500);
+        assert_eq!(scale.ticks, vec![0, 100, 200, 300, 400, 500]);
+    }
+    #[test]
+    fn test_never_zero() {
+        let scale = Scale::with_bounds(120, 470, ZeroPolicy::Never, None, None);
+        assert_eq!(scale.min, 100);
+        assert_eq!(scale.max, 500);
+    }
+    #[test]
+    fn test_forced_bounds() {
+        let scale = Scale::with_bounds(120, 470, ZeroPolicy::Auto, Some(150), Some(420));
+        assert_eq!(scale.min, 150);
+        assert_eq!(scale.max, 420);
+        assert_eq!(scale.ticks, vec![200, 300, 400]);
+    }
+    #[test]
+    fn test_forced_max_only() {
+        let scale = Scale::with_bounds(-30, 470, ZeroPolicy::Always, None, Some(300));
+        assert_eq!(scale.min, -100);
+        assert_eq!(scale.max, 300);
+        assert_eq!(scale.ticks, vec![-100, 0, 100, 200, 300]);
+    }
 }
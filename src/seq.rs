@@ -115,9 +115,12 @@ impl Seq {
             .ok_or_else(|| anyhow!("empty column"))
     }
     pub fn is_full_and_increasing(&self) -> bool {
+        if !matches!(self.ival.first(), Some(Some(_))) {
+            return false;
+        }
         for idx in 1..self.ival.len() {
             match (self.ival.get(idx - 1), self.ival.get(idx)) {
-                (Some(a), Some(b)) if a < b => {} // ok
+                (Some(Some(a)), Some(Some(b))) if a < b => {} // ok
                 _ => {
                     return false;
                 }
@@ -0,0 +1,42 @@
+use std::str::FromStr;
+
+/// a setting applying either to all series, or only to the
+/// series with the given header
+#[derive(Debug, Clone)]
+pub struct SeriesSetting<T> {
+    pub header: Option<String>,
+    pub value: T,
+}
+
+impl<T: FromStr<Err = String>> FromStr for SeriesSetting<T> {
+    type Err = String;
+    /// parse either `header=value` or just `value`
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.rsplit_once('=') {
+            Some((header, value)) => Ok(Self {
+                header: Some(header.to_string()),
+                value: value.parse()?,
+            }),
+            None => Ok(Self {
+                header: None,
+                value: s.parse()?,
+            }),
+        }
+    }
+}
+
+/// return the value of the setting for the given series, a setting
+/// specific to this series having precedence over a general one
+pub fn series_setting<T: Copy>(settings: &[SeriesSetting<T>], header: &str) -> Option<T> {
+    settings
+        .iter()
+        .rev()
+        .find(|setting| setting.header.as_deref() == Some(header))
+        .or_else(|| {
+            settings
+                .iter()
+                .rev()
+                .find(|setting| setting.header.is_none())
+        })
+        .map(|setting| setting.value)
+}
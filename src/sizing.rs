@@ -0,0 +1,90 @@
+/// how the SVG is sized by the page or viewer displaying it
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SvgSizing {
+    /// only a viewBox: the graph takes the room it's given, the
+    /// whole window in the HTML page
+    #[default]
+    Fit,
+    /// explicit width and height, in pixels, those of the graph
+    Fixed,
+    /// the width of the container, the height following, but not
+    /// so small or so big that the text would be unreadable
+    Responsive,
+}
+
+impl std::str::FromStr for SvgSizing {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_ref() {
+            "fit" => Ok(Self::Fit),
+            "fixed" => Ok(Self::Fixed),
+            "responsive" => Ok(Self::Responsive),
+            _ => Err(format!("unrecognized sizing {:?}", s)),
+        }
+    }
+}
+
+/// the smallest ratio of the natural width under which a
+/// responsive graph doesn't shrink
+pub static RESPONSIVE_MIN_RATIO: f64 = 0.5;
+
+/// the biggest ratio of the natural width over which a
+/// responsive graph doesn't grow
+pub static RESPONSIVE_MAX_RATIO: f64 = 2.0;
+
+/// a valid value of the `preserveAspectRatio` attribute, telling how
+/// the graph is placed when its box doesn't have the same ratio
+#[derive(Debug, Clone, PartialEq)]
+pub struct AspectRatio(String);
+
+impl AspectRatio {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::str::FromStr for AspectRatio {
+    type Err = String;
+    /// parse `none` or an alignment like `xMidYMin`, optionally
+    /// followed by `meet` or `slice`
+    fn from_str(s: &str) -> Result<Self, String> {
+        let error = || {
+            format!(
+                "invalid aspect ratio {:?}: expected \"none\" or an alignment like \"xMidYMin\", optionally followed by \"meet\" or \"slice\"",
+                s
+            )
+        };
+        let mut tokens = s.split_whitespace();
+        let align = tokens.next().ok_or_else(error)?;
+        let valid_align = align == "none"
+            || ["xMin", "xMid", "xMax"].iter().any(|x| {
+                ["YMin", "YMid", "YMax"]
+                    .iter()
+                    .any(|y| align == format!("{}{}", x, y))
+            });
+        if !valid_align {
+            return Err(error());
+        }
+        match (tokens.next(), tokens.next()) {
+            (None, _) => Ok(Self(align.to_string())),
+            (Some(mode @ ("meet" | "slice")), None) => Ok(Self(format!("{} {}", align, mode))),
+            _ => Err(error()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod sizing_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_aspect_ratio() {
+        let ratio: AspectRatio = "xMinYMid  slice".parse().unwrap();
+        assert_eq!(ratio.as_str(), "xMinYMid slice");
+        assert!("none".parse::<AspectRatio>().is_ok());
+        assert!("".parse::<AspectRatio>().is_err());
+        assert!("xMidYLeft".parse::<AspectRatio>().is_err());
+        assert!("xMidYMid cover".parse::<AspectRatio>().is_err());
+        assert!("xMidYMid meet slice".parse::<AspectRatio>().is_err());
+    }
+}
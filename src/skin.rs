@@ -1,9 +1,156 @@
+/// the colors of the series, on the dark theme
 pub static COLORS: &[&str] = &[
     "#e9967a", "#9cd3d3", "#e91e63", "#795548", "#8bc34a", "#00bcd4", "#ffc107", "#4e5050",
     "#317334", "#7126b1", "#ce0d08", "#959e75",
 ];
 
-pub static LEGEND_COLOR: &str = "#eee";
-pub static TICK_LINE_COLOR: &str = "#aad";
-pub static TICK_LABEL_COLOR: &str = "#eee";
-pub static DOCUMENT_STYLE: &str = "background-color: #222";
+/// the colors of the series, darker, for light backgrounds
+pub static LIGHT_COLORS: &[&str] = &[
+    "#d2691e", "#2a8c8c", "#c2185b", "#795548", "#558b2f", "#0097a7", "#e69500", "#4e5050",
+    "#317334", "#7126b1", "#ce0d08", "#6b7350",
+];
+
+/// saturated colors staying distinct when printed
+pub static PRINT_COLORS: &[&str] = &[
+    "#1f77b4", "#d62728", "#2ca02c", "#ff7f0e", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+    "#bcbd22", "#17becf", "#000000", "#393b79",
+];
+
+/// a color with an opacity, for shading areas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shade {
+    pub color: &'static str,
+    pub opacity: f32,
+}
+
+/// the colors of a graph
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub background: &'static str,
+    /// whether the background is dark, so that dark colors must be avoided
+    pub dark: bool,
+    /// color of the legend, titles and captions
+    pub text: &'static str,
+    /// color of the grid and tick lines
+    pub grid: &'static str,
+    /// color of the tick labels
+    pub tick: &'static str,
+    /// colors of the first series
+    pub palette: &'static [&'static str],
+    /// lightnesses of the generated colors, when the palette is too short
+    pub generated_lightnesses: [f64; 2],
+    pub stripe: Shade,
+    pub weekend: Shade,
+    pub night: Shade,
+    /// default color of the events
+    pub event: &'static str,
+    /// default color of the reference lines
+    pub hline: &'static str,
+}
+
+pub static DARK_THEME: Theme = Theme {
+    background: "#222",
+    dark: true,
+    text: "#eee",
+    grid: "#aad",
+    tick: "#eee",
+    palette: COLORS,
+    generated_lightnesses: [0.78, 0.64],
+    stripe: Shade {
+        color: "#fff",
+        opacity: 0.04,
+    },
+    weekend: Shade {
+        color: "#9cd3d3",
+        opacity: 0.07,
+    },
+    night: Shade {
+        color: "#000",
+        opacity: 0.25,
+    },
+    event: "#f0c674",
+    hline: "#ff8a65",
+};
+
+pub static LIGHT_THEME: Theme = Theme {
+    background: "#fafafa",
+    dark: false,
+    text: "#222",
+    grid: "#778",
+    tick: "#333",
+    palette: LIGHT_COLORS,
+    generated_lightnesses: [0.58, 0.45],
+    stripe: Shade {
+        color: "#000",
+        opacity: 0.03,
+    },
+    weekend: Shade {
+        color: "#2a8c8c",
+        opacity: 0.08,
+    },
+    night: Shade {
+        color: "#334",
+        opacity: 0.1,
+    },
+    event: "#b8860b",
+    hline: "#e64a19",
+};
+
+pub static PRINT_THEME: Theme = Theme {
+    background: "#fff",
+    dark: false,
+    text: "#000",
+    grid: "#888",
+    tick: "#000",
+    palette: PRINT_COLORS,
+    generated_lightnesses: [0.55, 0.4],
+    stripe: Shade {
+        color: "#000",
+        opacity: 0.04,
+    },
+    weekend: Shade {
+        color: "#000",
+        opacity: 0.08,
+    },
+    night: Shade {
+        color: "#000",
+        opacity: 0.12,
+    },
+    event: "#000",
+    hline: "#000",
+};
+
+/// the theme asked by the user
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+    Print,
+    /// dark or light depending on the preference of the user, which
+    /// is only known in HTML (the SVG alone is dark)
+    Auto,
+}
+
+impl ThemeName {
+    pub fn theme(self) -> &'static Theme {
+        match self {
+            Self::Dark | Self::Auto => &DARK_THEME,
+            Self::Light => &LIGHT_THEME,
+            Self::Print => &PRINT_THEME,
+        }
+    }
+}
+
+impl std::str::FromStr for ThemeName {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_ref() {
+            "dark" => Ok(Self::Dark),
+            "light" => Ok(Self::Light),
+            "print" => Ok(Self::Print),
+            "auto" => Ok(Self::Auto),
+            _ => Err(format!("unrecognized theme {:?}", s)),
+        }
+    }
+}
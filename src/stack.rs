@@ -0,0 +1,167 @@
+use crate::*;
+
+/// how the y series are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ChartMode {
+    /// one line per series
+    #[default]
+    Lines,
+    /// series stacked on each other as areas
+    Stacked,
+    /// stacked areas, normalized so that they sum to 100%
+    Stacked100,
+    /// independent points, x not having to be increasing
+    Scatter,
+}
+
+impl std::str::FromStr for ChartMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_ref() {
+            "lines" => Ok(Self::Lines),
+            "stacked" => Ok(Self::Stacked),
+            "stacked100" => Ok(Self::Stacked100),
+            "scatter" => Ok(Self::Scatter),
+            _ => Err(format!("unrecognized chart mode {:?}", s)),
+        }
+    }
+}
+
+/// what value a missing cell is given in a stack
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MissingPolicy {
+    #[default]
+    Zero,
+    /// interpolate between the surrounding values of the series,
+    /// zero being used before the first one and after the last one
+    Interpolate,
+}
+
+impl std::str::FromStr for MissingPolicy {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_ref() {
+            "zero" => Ok(Self::Zero),
+            "interpolate" => Ok(Self::Interpolate),
+            _ => Err(format!("unrecognized missing value policy {:?}", s)),
+        }
+    }
+}
+
+/// the y series of a table, stacked on each other
+#[derive(Debug)]
+pub struct Stack {
+    /// for each y series, the top of its layer, for each row
+    pub tops: Vec<Vec<f64>>,
+    /// for each row, the sum of the values
+    pub totals: Vec<f64>,
+    pub normalized: bool,
+}
+
+impl Stack {
+    pub fn new(tbl: &Tbl, missing: MissingPolicy, normalized: bool) -> Self {
+        let x_seq = tbl.x_seq();
+        let row_count = x_seq.len();
+        let layers: Vec<Vec<f64>> = tbl
+            .y_seqs()
+            .map(|seq| fill(&x_seq.ival, &seq.ival, missing))
+            .collect();
+        let totals: Vec<f64> = (0..row_count)
+            .map(|row| layers.iter().map(|layer| layer[row]).sum())
+            .collect();
+        let mut tops = Vec::with_capacity(layers.len());
+        let mut cumul = vec![0.0; row_count];
+        for layer in layers {
+            for row in 0..row_count {
+                cumul[row] += if normalized {
+                    if totals[row] == 0.0 {
+                        0.0
+                    } else {
+                        layer[row] * 100.0 / totals[row]
+                    }
+                } else {
+                    layer[row]
+                };
+            }
+            tops.push(cumul.clone());
+        }
+        Self {
+            tops,
+            totals,
+            normalized,
+        }
+    }
+    /// return the bottom of the layer of the given series, for each row
+    pub fn bottoms(&self, seq_idx: usize) -> Option<&Vec<f64>> {
+        if seq_idx == 0 {
+            None
+        } else {
+            self.tops.get(seq_idx - 1)
+        }
+    }
+    /// the top of the y axis, when the stack imposes one: a normalized
+    /// stack stops at 100%, instead of the next round tick
+    pub fn y_ceiling(&self) -> Option<i64> {
+        if self.normalized {
+            Some(100)
+        } else {
+            None
+        }
+    }
+    /// return the range of the stacked values, which always includes zero
+    pub fn y_min_max(&self) -> (i64, i64) {
+        if self.normalized {
+            return (0, 100);
+        }
+        self.tops
+            .iter()
+            .flat_map(|layer| layer.iter())
+            .fold((0, 0), |(min, max), &v| {
+                (min.min(v.floor() as i64), max.max(v.ceil() as i64))
+            })
+    }
+}
+
+/// build the values of a series for all rows, according to the policy
+fn fill(xs: &[Option<i64>], ys: &[Option<i64>], missing: MissingPolicy) -> Vec<f64> {
+    let mut values: Vec<f64> = ys.iter().map(|y| y.map_or(0.0, |y| y as f64)).collect();
+    if missing == MissingPolicy::Interpolate {
+        let mut previous: Option<usize> = None;
+        for idx in 0..ys.len() {
+            if ys[idx].is_none() {
+                continue;
+            }
+            if let Some(p) = previous {
+                if let (Some(x0), Some(x1)) = (xs[p], xs[idx]) {
+                    let (y0, y1) = (values[p], values[idx]);
+                    for i in p + 1..idx {
+                        if let Some(x) = xs[i] {
+                            values[i] = y0 + (y1 - y0) * (x - x0) as f64 / (x1 - x0) as f64;
+                        }
+                    }
+                }
+            }
+            previous = Some(idx);
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod stack_tests {
+    use super::*;
+
+    #[test]
+    fn test_fill() {
+        let xs = vec![Some(0), Some(1), Some(2), Some(4), Some(5)];
+        let ys = vec![None, Some(10), None, Some(40), None];
+        assert_eq!(
+            fill(&xs, &ys, MissingPolicy::Zero),
+            vec![0.0, 10.0, 0.0, 40.0, 0.0]
+        );
+        assert_eq!(
+            fill(&xs, &ys, MissingPolicy::Interpolate),
+            vec![0.0, 10.0, 20.0, 40.0, 0.0],
+        );
+    }
+}
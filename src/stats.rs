@@ -0,0 +1,137 @@
+use std::io;
+
+/// summary statistics of the values of a y series
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesStats {
+    pub header: String,
+    pub count: usize,
+    pub min: i64,
+    pub max: i64,
+    pub mean: f64,
+    pub last: i64,
+    pub p95: i64,
+}
+
+impl SeriesStats {
+    /// compute the statistics of the values, given in x order,
+    /// or return `None` when there's no value
+    pub fn new(header: &str, values: &[i64]) -> Option<Self> {
+        let last = *values.last()?;
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+        let count = sorted.len();
+        // nearest-rank percentile
+        let p95_rank = (count * 95).div_ceil(100).max(1);
+        Some(Self {
+            header: header.to_string(),
+            count,
+            min: sorted[0],
+            max: sorted[count - 1],
+            mean: sorted.iter().map(|&v| v as f64).sum::<f64>() / count as f64,
+            last,
+            p95: sorted[p95_rank - 1],
+        })
+    }
+}
+
+/// how statistics are written
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatsFormat {
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for StatsFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_ref() {
+            "text" | "txt" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("unrecognized stats format {:?}", s)),
+        }
+    }
+}
+
+/// write a string as a JSON literal
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+pub fn write_stats<W: io::Write>(
+    mut w: W,
+    stats: &[SeriesStats],
+    format: StatsFormat,
+) -> io::Result<()> {
+    match format {
+        StatsFormat::Text => {
+            for s in stats {
+                writeln!(
+                    w,
+                    "{}: count={} min={} max={} mean={:.2} last={} p95={}",
+                    s.header, s.count, s.min, s.max, s.mean, s.last, s.p95,
+                )?;
+            }
+        }
+        StatsFormat::Json => {
+            writeln!(w, "[")?;
+            for (idx, s) in stats.iter().enumerate() {
+                let separator = if idx + 1 < stats.len() { "," } else { "" };
+                writeln!(
+                    w,
+                    "  {{\"header\": {}, \"count\": {}, \"min\": {}, \"max\": {}, \"mean\": {}, \"last\": {}, \"p95\": {}}}{}",
+                    json_string(&s.header),
+                    s.count,
+                    s.min,
+                    s.max,
+                    s.mean,
+                    s.last,
+                    s.p95,
+                    separator,
+                )?;
+            }
+            writeln!(w, "]")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod stats_tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let values: Vec<i64> = (1..=20).rev().collect();
+        let stats = SeriesStats::new("x", &values).unwrap();
+        assert_eq!(stats.min, 1);
+        assert_eq!(stats.max, 20);
+        assert_eq!(stats.mean, 10.5);
+        assert_eq!(stats.last, 1);
+        assert_eq!(stats.p95, 19);
+        assert_eq!(SeriesStats::new("x", &[]), None);
+    }
+    #[test]
+    fn test_json() {
+        let stats = vec![SeriesStats::new("a \"b\"", &[3, 1]).unwrap()];
+        let mut out = Vec::new();
+        write_stats(&mut out, &stats, StatsFormat::Json).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[\n  {\"header\": \"a \\\"b\\\"\", \"count\": 2, \"min\": 1, \"max\": 3, \"mean\": 2, \"last\": 1, \"p95\": 3}\n]\n",
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use {
+    crate::*,
+    chrono::{Datelike, NaiveDate, Weekday},
+};
+
+/// the period of the alternating background bands of a time axis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StripePeriod {
+    Day,
+    Week,
+}
+
+impl std::str::FromStr for StripePeriod {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_ref() {
+            "day" | "days" => Ok(Self::Day),
+            "week" | "weeks" => Ok(Self::Week),
+            _ => Err(format!("unrecognized stripe period {:?}", s)),
+        }
+    }
+}
+
+/// a range of hours of the day, which may wrap around midnight
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HourRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl std::str::FromStr for HourRange {
+    type Err = String;
+    /// parse a range like "20-7"
+    fn from_str(s: &str) -> Result<Self, String> {
+        let err = || format!("expected an hour range like \"20-7\", got {:?}", s);
+        let (start, end) = s.split_once('-').ok_or_else(err)?;
+        let start: u32 = start.trim().parse().map_err(|_| err())?;
+        let end: u32 = end.trim().parse().map_err(|_| err())?;
+        if start > 24 || end > 24 || start == end {
+            return Err(err());
+        }
+        Ok(Self { start, end })
+    }
+}
+
+/// the whole day, as a range of timestamps
+fn day_band(date: NaiveDate, tz: Timezone) -> (i64, i64) {
+    (tz.day_start(date), tz.day_start(date.succ_opt().unwrap()))
+}
+
+/// the days overlapping the [min, max] range of timestamps
+fn days(min: i64, max: i64, tz: Timezone) -> Vec<NaiveDate> {
+    let (first, last) = match (tz.date(min), tz.date(max)) {
+        (Some(first), Some(last)) => (first, last),
+        _ => {
+            return Vec::new();
+        }
+    };
+    first.iter_days().take_while(|d| *d <= last).collect()
+}
+
+/// restrict the bands to the [min, max] range, removing the empty ones
+fn clamp(bands: Vec<(i64, i64)>, min: i64, max: i64) -> Vec<(i64, i64)> {
+    bands
+        .into_iter()
+        .map(|(start, end)| (start.max(min), end.min(max)))
+        .filter(|(start, end)| start < end)
+        .collect()
+}
+
+/// compute one band every other period, as ranges of timestamps. The
+/// shaded periods don't depend on the range, so that charts of close
+/// ranges are consistent
+pub fn period_bands(min: i64, max: i64, tz: Timezone, period: StripePeriod) -> Vec<(i64, i64)> {
+    let bands = days(min, max, tz)
+        .into_iter()
+        .filter(|date| {
+            let days_from_ce = date.num_days_from_ce();
+            match period {
+                StripePeriod::Day => days_from_ce % 2 == 0,
+                StripePeriod::Week => {
+                    let monday = days_from_ce - date.weekday().num_days_from_monday() as i32;
+                    (monday / 7) % 2 == 0
+                }
+            }
+        })
+        .map(|date| day_band(date, tz))
+        .collect();
+    clamp(merge(bands), min, max)
+}
+
+/// compute the bands covering saturdays and sundays
+pub fn weekend_bands(min: i64, max: i64, tz: Timezone) -> Vec<(i64, i64)> {
+    let bands = days(min, max, tz)
+        .into_iter()
+        .filter(|date| matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+        .map(|date| day_band(date, tz))
+        .collect();
+    clamp(merge(bands), min, max)
+}
+
+/// compute the bands covering the given hours of every day
+pub fn hour_bands(min: i64, max: i64, tz: Timezone, hours: HourRange) -> Vec<(i64, i64)> {
+    let mut bands = Vec::new();
+    for date in days(min, max, tz) {
+        // hours are computed from the local time, as days may not
+        // last 24 hours when the daylight saving time changes
+        let hour = |h: u32| tz.hour_start(date, h);
+        if hours.start < hours.end {
+            bands.push((hour(hours.start), hour(hours.end)));
+        } else {
+            // the range wraps around midnight
+            bands.push((hour(0), hour(hours.end)));
+            bands.push((hour(hours.start), hour(24)));
+        }
+    }
+    clamp(merge(bands), min, max)
+}
+
+/// merge the contiguous bands
+fn merge(bands: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    let mut merged: Vec<(i64, i64)> = Vec::new();
+    for (start, end) in bands {
+        match merged.last_mut() {
+            Some(last) if last.1 >= start => {
+                last.1 = last.1.max(end);
+            }
+            _ => {
+                merged.push((start, end));
+            }
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod stripes_tests {
+    use {
+        super::*,
+        chrono::{DateTime, FixedOffset},
+    };
+
+    fn ms(s: &str) -> i64 {
+        DateTime::parse_from_rfc3339(s).unwrap().timestamp_millis()
+    }
+
+    #[test]
+    fn test_weekend_bands() {
+        let tz = Timezone::Fixed(FixedOffset::east_opt(2 * 3600).unwrap());
+        // 2020-12-25 was a friday
+        let bands = weekend_bands(ms("2020-12-25T10:00:00Z"), ms("2020-12-28T10:00:00Z"), tz);
+        assert_eq!(
+            bands,
+            vec![(
+                ms("2020-12-26T00:00:00+02:00"),
+                ms("2020-12-28T00:00:00+02:00")
+            )],
+        );
+    }
+    #[test]
+    fn test_week_bands() {
+        let tz = Timezone::Fixed(FixedOffset::east_opt(0).unwrap());
+        let bands = period_bands(
+            ms("2020-12-01T00:00:00Z"),
+            ms("2021-01-01T00:00:00Z"),
+            tz,
+            StripePeriod::Week,
+        );
+        // every other week, from monday to monday
+        assert_eq!(
+            bands,
+            vec![
+                (ms("2020-12-01T00:00:00Z"), ms("2020-12-07T00:00:00Z")),
+                (ms("2020-12-14T00:00:00Z"), ms("2020-12-21T00:00:00Z")),
+                (ms("2020-12-28T00:00:00Z"), ms("2021-01-01T00:00:00Z")),
+            ],
+        );
+    }
+    #[test]
+    fn test_night_bands() {
+        let tz = Timezone::Fixed(FixedOffset::east_opt(0).unwrap());
+        let hours: HourRange = "20-7".parse().unwrap();
+        let bands = hour_bands(
+            ms("2020-12-25T10:00:00Z"),
+            ms("2020-12-26T10:00:00Z"),
+            tz,
+            hours,
+        );
+        assert_eq!(
+            bands,
+            vec![(ms("2020-12-25T20:00:00Z"), ms("2020-12-26T07:00:00Z"))],
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use {
+    crate::*,
+    anyhow::{Context, Result},
+    std::{fs, path::Path},
+};
+
+/// changes to the default look of a series
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StyleOverrides {
+    pub color: Option<String>,
+    pub width: Option<f64>,
+    pub opacity: Option<f64>,
+    /// the stroke-dasharray, `Some(None)` forcing a solid line
+    pub dash_array: Option<Option<String>>,
+    /// the marker shape, `Some(None)` removing the markers
+    pub marker: Option<Option<MarkerShape>>,
+    pub interpolation: Option<Interpolation>,
+}
+
+impl StyleOverrides {
+    /// set the fields which are set in `other`
+    fn merge(&mut self, other: &Self) {
+        if other.color.is_some() {
+            self.color = other.color.clone();
+        }
+        if other.width.is_some() {
+            self.width = other.width;
+        }
+        if other.opacity.is_some() {
+            self.opacity = other.opacity;
+        }
+        if other.dash_array.is_some() {
+            self.dash_array = other.dash_array.clone();
+        }
+        if other.marker.is_some() {
+            self.marker = other.marker;
+        }
+        if other.interpolation.is_some() {
+            self.interpolation = other.interpolation;
+        }
+    }
+}
+
+impl std::str::FromStr for StyleOverrides {
+    type Err = String;
+    /// parse a comma separated list of `key=value`
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut overrides = Self::default();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected key=value, got {:?}", part))?;
+            let value = value.trim();
+            let number = |name: &str| -> Result<f64, String> {
+                value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|v| *v >= 0.0)
+                    .ok_or_else(|| format!("invalid {}: {:?}", name, value))
+            };
+            match key.trim().to_lowercase().as_ref() {
+                "color" | "colour" => {
+                    overrides.color = Some(value.to_string());
+                }
+                "width" => {
+                    overrides.width = Some(number("width")?);
+                }
+                "opacity" => {
+                    overrides.opacity = Some(number("opacity")?.min(1.0));
+                }
+                "dash" | "dasharray" => {
+                    overrides.dash_array = Some(match value {
+                        "none" | "solid" => None,
+                        _ => Some(value.to_string()),
+                    });
+                }
+                "marker" => {
+                    overrides.marker = Some(match value {
+                        "none" => None,
+                        _ => Some(value.parse()?),
+                    });
+                }
+                "interpolation" => {
+                    overrides.interpolation = Some(value.parse()?);
+                }
+                _ => {
+                    return Err(format!("unknown style property {:?}", key));
+                }
+            }
+        }
+        Ok(overrides)
+    }
+}
+
+/// style overrides applying to the series whose header matches a
+/// glob pattern
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleRule {
+    pub pattern: String,
+    pub overrides: StyleOverrides,
+}
+
+impl std::str::FromStr for StyleRule {
+    type Err = String;
+    /// parse `pattern:key=value,key=value`
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (pattern, overrides) = s
+            .rsplit_once(':')
+            .ok_or_else(|| format!("expected a style like \"errors:color=#f00\", got {:?}", s))?;
+        Ok(Self {
+            pattern: pattern.trim().to_string(),
+            overrides: overrides.parse()?,
+        })
+    }
+}
+
+impl StyleRule {
+    /// read rules from a file, one per line, empty lines and lines
+    /// starting with '#' being ignored
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Vec<Self>> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("can't read style file {:?}", path))?;
+        content
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (idx, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(idx, line)| {
+                line.parse()
+                    .map_err(|e| anyhow::anyhow!("{:?} line {}: {}", path, idx + 1, e))
+            })
+            .collect()
+    }
+}
+
+/// whether the text matches the glob pattern, where `*` matches any
+/// sequence of chars and `?` any single char
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let (pattern, text): (Vec<char>, Vec<char>) =
+        (pattern.chars().collect(), text.chars().collect());
+    let (mut p, mut t) = (0, 0);
+    // position of the last star in the pattern, and of the text
+    // when it was met, to backtrack
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// the overrides applying to the series with the given header, the
+/// later rules having precedence
+pub fn series_overrides(rules: &[StyleRule], header: &str) -> StyleOverrides {
+    let mut overrides = StyleOverrides::default();
+    for rule in rules
+        .iter()
+        .filter(|rule| glob_matches(&rule.pattern, header))
+    {
+        overrides.merge(&rule.overrides);
+    }
+    overrides
+}
+
+/// how a series is drawn
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesStyle {
+    pub color: String,
+    pub width: f64,
+    pub opacity: f64,
+    pub dash_array: Option<String>,
+    /// the shape of the markers, none when the points aren't marked
+    pub marker: Option<MarkerShape>,
+    pub interpolation: Interpolation,
+}
+
+impl SeriesStyle {
+    pub fn with_overrides(mut self, overrides: StyleOverrides) -> Self {
+        if let Some(color) = overrides.color {
+            self.color = color;
+        }
+        if let Some(width) = overrides.width {
+            self.width = width;
+        }
+        if let Some(opacity) = overrides.opacity {
+            self.opacity = opacity;
+        }
+        if let Some(dash_array) = overrides.dash_array {
+            self.dash_array = dash_array;
+        }
+        if let Some(marker) = overrides.marker {
+            self.marker = marker;
+        }
+        if let Some(interpolation) = overrides.interpolation {
+            self.interpolation = interpolation;
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod style_tests {
+    use super::*;
+
+    #[test]
+    fn test_glob() {
+        assert!(glob_matches("errors", "errors"));
+        assert!(!glob_matches("errors", "errors_5xx"));
+        assert!(glob_matches("errors*", "errors_5xx"));
+        assert!(glob_matches("*_5?x", "errors_5xx"));
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("a*b*c", "abxbc"));
+        assert!(!glob_matches("a*b*c", "abxb"));
+    }
+    #[test]
+    fn test_parse_rule() {
+        let rule: StyleRule = "errors*:color=#f00, width=2,dash=4 2,marker=none"
+            .parse()
+            .unwrap();
+        assert_eq!(rule.pattern, "errors*");
+        assert_eq!(rule.overrides.color.as_deref(), Some("#f00"));
+        assert_eq!(rule.overrides.width, Some(2.0));
+        assert_eq!(rule.overrides.dash_array, Some(Some("4 2".to_string())));
+        assert_eq!(rule.overrides.marker, Some(None));
+        assert!("errors".parse::<StyleRule>().is_err());
+        assert!("errors:size=3".parse::<StyleRule>().is_err());
+    }
+    #[test]
+    fn test_precedence() {
+        let rules: Vec<StyleRule> = vec![
+            "*:width=1,opacity=0.5".parse().unwrap(),
+            "total:width=4".parse().unwrap(),
+        ];
+        let overrides = series_overrides(&rules, "total");
+        assert_eq!(overrides.width, Some(4.0));
+        assert_eq!(overrides.opacity, Some(0.5));
+        assert_eq!(series_overrides(&rules, "errors").width, Some(1.0));
+    }
+}
@@ -51,14 +51,29 @@ impl Tbl {
     pub fn x_seq(&self) -> &Seq {
         &self.seqs[0]
     }
+    pub fn seqs(&self) -> std::slice::Iter<'_, seq::Seq> {
+        self.seqs.iter()
+    }
     pub fn y_seqs(&self) -> std::iter::Skip<std::slice::Iter<'_, seq::Seq>> {
         self.seqs.iter().skip(1)
     }
+    /// return the y seq of given index (among y sequences)
+    pub fn y_seq(&self, idx: usize) -> &Seq {
+        &self.seqs[idx + 1]
+    }
     pub fn y_min_max(&self) -> (i64, i64) {
-        let mut y_seqs = self.y_seqs();
-        let first_y = y_seqs.next().unwrap();
-        y_seqs.fold((first_y.min, first_y.max), |(min, max), seq| {
-            (min.min(seq.min), max.max(seq.max))
-        })
+        self.y_min_max_where(|_| true).unwrap()
+    }
+    /// return the min and max of the y sequences whose index (among
+    /// y sequences) is accepted by the filter, if any
+    pub fn y_min_max_where<F: Fn(usize) -> bool>(&self, filter: F) -> Option<(i64, i64)> {
+        self.y_seqs()
+            .enumerate()
+            .filter(|(idx, _)| filter(*idx))
+            .fold(None, |mm, (_, seq)| {
+                Some(mm.map_or((seq.min, seq.max), |(min, max): (i64, i64)| {
+                    (min.min(seq.min), max.max(seq.max))
+                }))
+            })
     }
 }
@@ -0,0 +1,52 @@
+/// widths of the printable ASCII chars (from space to tilde) in
+/// Helvetica, in thousandths of em. Arial has the same metrics.
+static HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, // ' '../
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, // 0..?
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, // @..O
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556, // P.._
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556, // `..o
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584, // p..~
+];
+
+/// width of the chars which aren't in the table
+const DEFAULT_WIDTH: u16 = 556;
+
+/// width of the east asian chars, which are usually square
+const WIDE_WIDTH: u16 = 1000;
+
+/// browsers may fall back to wider fonts than Helvetica, so we keep
+/// a little room
+const SAFETY_RATIO: f64 = 1.05;
+
+fn char_width(c: char) -> u16 {
+    match c as u32 {
+        code @ 32..=126 => HELVETICA_WIDTHS[(code - 32) as usize],
+        0x1100..=0x115F | 0x2E80..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFF00..=0xFF60 => {
+            WIDE_WIDTH
+        }
+        _ => DEFAULT_WIDTH,
+    }
+}
+
+/// estimate the width, in pixels, of a text written in the
+/// default font at the given size
+pub fn text_width(text: &str, font_size: f64) -> f64 {
+    let thousandths: u32 = text.chars().map(|c| char_width(c) as u32).sum();
+    thousandths as f64 * font_size * SAFETY_RATIO / 1000.0
+}
+
+#[cfg(test)]
+mod text_width_tests {
+    use super::*;
+
+    #[test]
+    fn test_text_width() {
+        assert_eq!(text_width("", 10.0), 0.0);
+        // digits all have the same width
+        assert_eq!(text_width("1000", 10.0), text_width("9999", 10.0));
+        assert!(text_width("WWW", 10.0) > text_width("iii", 10.0) * 3.0);
+        assert!(text_width("日本", 10.0) > text_width("ab", 10.0));
+        assert_eq!(text_width("mm", 20.0), 2.0 * text_width("mm", 10.0));
+    }
+}
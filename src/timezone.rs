@@ -0,0 +1,119 @@
+use chrono::{
+    DateTime, Duration, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime, SecondsFormat,
+    TimeZone, Utc,
+};
+
+/// the timezone in which dates are displayed and days are computed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Timezone {
+    /// the timezone of the system, with its daylight saving changes
+    Local,
+    /// a fixed offset from UTC
+    Fixed(FixedOffset),
+}
+
+impl std::str::FromStr for Timezone {
+    type Err = String;
+    /// parse "utc", "local" or an offset like "+02:00"
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_ref() {
+            "utc" | "z" => Ok(Self::Fixed(FixedOffset::east_opt(0).unwrap())),
+            "local" => Ok(Self::Local),
+            _ => s.parse().map(Self::Fixed).map_err(|_| {
+                format!(
+                    "expected \"utc\", \"local\", or an offset like \"+02:00\", got {:?}",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+impl Timezone {
+    /// the date at the given timestamp, in milliseconds
+    pub fn date(self, ms: i64) -> Option<NaiveDate> {
+        let dt = DateTime::<Utc>::from_timestamp(ms.div_euclid(1000), 0)?;
+        Some(match self {
+            Self::Local => dt.with_timezone(&Local).date_naive(),
+            Self::Fixed(offset) => dt.with_timezone(&offset).date_naive(),
+        })
+    }
+    /// the timestamp, in milliseconds, of the given hour of a day, the
+    /// hour 24 being the start of the next day.
+    /// When the hour is skipped by a daylight saving change, the time
+    /// of the change is returned
+    pub fn hour_start(self, date: NaiveDate, hour: u32) -> i64 {
+        let naive = date.and_hms_opt(0, 0, 0).unwrap() + Duration::hours(hour as i64);
+        match self {
+            Self::Local => local_millis(&Local, naive),
+            Self::Fixed(offset) => local_millis(&offset, naive),
+        }
+    }
+    /// the start of a day, as a timestamp in milliseconds
+    pub fn day_start(self, date: NaiveDate) -> i64 {
+        self.hour_start(date, 0)
+    }
+    /// write a timestamp as a RFC 3339 date
+    pub fn format(self, ms: i64) -> Option<String> {
+        let dt = match Utc.timestamp_millis_opt(ms) {
+            LocalResult::Single(dt) => dt,
+            _ => {
+                return None;
+            }
+        };
+        Some(match self {
+            Self::Local => dt
+                .with_timezone(&Local)
+                .to_rfc3339_opts(SecondsFormat::Secs, true),
+            Self::Fixed(offset) => dt
+                .with_timezone(&offset)
+                .to_rfc3339_opts(SecondsFormat::Secs, true),
+        })
+    }
+}
+
+/// the timestamp of a local time, the earliest one when it's ambiguous,
+/// the end of the gap when it's skipped
+fn local_millis<Tz: TimeZone>(tz: &Tz, naive: NaiveDateTime) -> i64 {
+    let mut naive = naive;
+    // gaps are never longer than a few hours
+    for _ in 0..24 {
+        if let Some(dt) = tz.from_local_datetime(&naive).earliest() {
+            return dt.timestamp_millis();
+        }
+        naive += Duration::minutes(15);
+    }
+    naive.and_utc().timestamp_millis()
+}
+
+#[cfg(test)]
+mod timezone_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!("local".parse(), Ok(Timezone::Local));
+        assert_eq!(
+            "UTC".parse(),
+            Ok(Timezone::Fixed(FixedOffset::east_opt(0).unwrap()))
+        );
+        assert_eq!(
+            "+05:30".parse(),
+            Ok(Timezone::Fixed(
+                FixedOffset::east_opt(5 * 3600 + 1800).unwrap()
+            ))
+        );
+        assert!("Mars/Olympus".parse::<Timezone>().is_err());
+    }
+    #[test]
+    fn test_local_days() {
+        // whatever the timezone of the system, days start at midnight
+        let tz = Timezone::Local;
+        let date = NaiveDate::from_ymd_opt(2021, 3, 28).unwrap();
+        let start = tz.day_start(date);
+        assert_eq!(tz.date(start), Some(date));
+        assert_eq!(tz.date(start - 1000), date.pred_opt());
+        let next = tz.day_start(date.succ_opt().unwrap());
+        assert_eq!(tz.date(next - 1000), Some(date));
+    }
+}
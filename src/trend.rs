@@ -0,0 +1,54 @@
+/// a straight line fitting a set of points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trend {
+    pub slope: f64,
+    pub intercept: f64,
+}
+
+impl Trend {
+    /// compute the least-squares line of the points, if there are
+    /// at least two distinct x
+    pub fn new(points: &[(f64, f64)]) -> Option<Self> {
+        let n = points.len() as f64;
+        if n < 2.0 {
+            return None;
+        }
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let mut sxx = 0.0;
+        let mut sxy = 0.0;
+        for (x, y) in points {
+            sxx += (x - mean_x) * (x - mean_x);
+            sxy += (x - mean_x) * (y - mean_y);
+        }
+        if sxx == 0.0 {
+            return None;
+        }
+        let slope = sxy / sxx;
+        Some(Self {
+            slope,
+            intercept: mean_y - slope * mean_x,
+        })
+    }
+    pub fn y(&self, x: f64) -> f64 {
+        self.slope * x + self.intercept
+    }
+}
+
+#[cfg(test)]
+mod trend_tests {
+    use super::*;
+
+    #[test]
+    fn test_trend() {
+        let trend = Trend::new(&[(0.0, 1.0), (1.0, 3.0), (2.0, 5.0), (2.0, 5.0)]).unwrap();
+        assert_eq!(trend.slope, 2.0);
+        assert_eq!(trend.intercept, 1.0);
+        assert_eq!(trend.y(10.0), 21.0);
+    }
+    #[test]
+    fn test_no_trend() {
+        assert_eq!(Trend::new(&[(3.0, 1.0), (3.0, 2.0)]), None);
+        assert_eq!(Trend::new(&[(3.0, 1.0)]), None);
+    }
+}
@@ -140,6 +140,30 @@ pub fn unoverlap(mut dots: Vec<i64>, margin: i64) -> Option<Vec<i64>> {
     Some(dots)
 }
 
+/// move the positions, given in any order, so that they're at least
+/// `margin` apart while staying in the [min, max] range.
+/// Unlike `unoverlap`, no position is fixed.
+/// The moved positions are returned in the original order, or `None`
+/// when there's not enough room.
+pub fn spread(positions: &[i64], margin: i64, min: i64, max: i64) -> Option<Vec<i64>> {
+    let mut order: Vec<usize> = (0..positions.len()).collect();
+    order.sort_by_key(|&idx| positions[idx]);
+    // two sentinels, which unoverlap won't move, keep the positions in range
+    let mut dots = Vec::with_capacity(positions.len() + 2);
+    dots.push(min - margin);
+    for &idx in &order {
+        let dot = positions[idx].clamp(min, max);
+        dots.push(dot.max(dots[dots.len() - 1] + 1));
+    }
+    dots.push((max + margin).max(dots[dots.len() - 1] + 1));
+    let dots = unoverlap(dots, margin)?;
+    let mut spread = vec![0; positions.len()];
+    for (rank, &idx) in order.iter().enumerate() {
+        spread[idx] = dots[rank + 1];
+    }
+    Some(spread)
+}
+
 #[cfg(test)]
 mod unoverlap_tests {
     use super::*;
@@ -190,4 +214,10 @@ mod unoverlap_tests {
             Some(vec![0, 10, 20, 30, 70, 80, 90, 100]),
         );
     }
+    #[test]
+    fn test_spread() {
+        assert_eq!(spread(&[55, 50, 200], 10, 0, 100), Some(vec![58, 48, 100]));
+        assert_eq!(spread(&[3, 5], 10, 0, 100), Some(vec![0, 10]));
+        assert_eq!(spread(&[3, 5, 7], 60, 0, 100), None);
+    }
 }